@@ -0,0 +1,26 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use std::io;
+
+
+// Reading arbitrary bytes must fail with an error instead of panicking or allocating
+// without bound. The limits are kept small so that every allocation stays cheap.
+fuzz_target!(|data: &[u8]| {
+    let options = bmfa::ReadOptions {
+        strict: true,
+        limits: bmfa::ReadLimits {
+            max_image_dimension: 1024,
+            max_decompressed_size: 16 << 20,
+            max_glyph_count: 1 << 16,
+            max_metadata_size: 1 << 20,
+            max_image_entry_size: 16 << 20,
+        },
+    };
+    let _ = bmfa::from_reader_with_options(io::Cursor::new(data), &options);
+
+    if let Ok(mut atlas) = bmfa::lazy::from_reader_with_options(io::Cursor::new(data), &options) {
+        for page in 0..atlas.page_count() {
+            let _ = atlas.page(page);
+        }
+    }
+});
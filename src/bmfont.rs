@@ -0,0 +1,867 @@
+use crate::{
+    AtlasLayout,
+    BitmapFontAtlas,
+    BitmapFontAtlasImage,
+    BitmapFontAtlasMetadata,
+    Error,
+    ErrorKind,
+    GlyphMetadata,
+    GlyphRect,
+    Origin,
+    PixelFormat,
+};
+use image::GenericImageView;
+use image::codecs::png;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+
+/// The `BmFontInfo` struct holds the `info` block of an AngelCode BMFont descriptor, which
+/// describes how the font was rasterized.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BmFontInfo {
+    /// The name of the true type font.
+    pub face: String,
+    /// The size of the font, in pixels. A negative size means the font was rasterized so
+    /// that its character height, rather than its cell height, matches the size.
+    pub size: isize,
+    /// The padding around each character, in pixels, in the order up, right, down, left.
+    pub padding: [usize; 4],
+    /// The spacing between characters, in pixels, in the order horizontal, vertical.
+    pub spacing: [usize; 2],
+}
+
+impl BmFontInfo {
+    pub fn new(face: &str, size: isize) -> BmFontInfo {
+        BmFontInfo {
+            face: String::from(face),
+            size: size,
+            padding: [0; 4],
+            spacing: [0; 2],
+        }
+    }
+}
+
+/// The `BmFontCommon` struct holds the `common` block of an AngelCode BMFont descriptor,
+/// which holds the line metrics shared by every character.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct BmFontCommon {
+    /// The distance between the baselines of consecutive lines of text, in pixels.
+    pub line_height: usize,
+    /// The distance from the top of a line to the baseline, in pixels.
+    pub base: usize,
+    /// The width of each page, in pixels.
+    pub scale_width: usize,
+    /// The height of each page, in pixels.
+    pub scale_height: usize,
+}
+
+impl BmFontCommon {
+    pub fn new(line_height: usize, base: usize, scale_width: usize, scale_height: usize) -> BmFontCommon {
+        BmFontCommon {
+            line_height: line_height,
+            base: base,
+            scale_width: scale_width,
+            scale_height: scale_height,
+        }
+    }
+}
+
+/// A `BmFontChar` is one `char` entry of an AngelCode BMFont descriptor. All of its
+/// fields are measured in pixels.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct BmFontChar {
+    /// The unicode code point of the character.
+    pub id: usize,
+    /// The horizontal position of the left edge of the character image on its page.
+    pub x: usize,
+    /// The vertical position of the top edge of the character image on its page.
+    pub y: usize,
+    /// The width of the character image.
+    pub width: usize,
+    /// The height of the character image.
+    pub height: usize,
+    /// The horizontal distance from the pen position to the left edge of the character image.
+    pub x_offset: isize,
+    /// The vertical distance from the top of the line to the top edge of the character image.
+    pub y_offset: isize,
+    /// The horizontal distance to move the pen after drawing the character.
+    pub x_advance: isize,
+    /// The page the character image is stored on.
+    pub page: usize,
+}
+
+/// A `BmFontKerning` is one `kerning` entry of an AngelCode BMFont descriptor.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct BmFontKerning {
+    /// The code point of the first character of the pair.
+    pub first: usize,
+    /// The code point of the second character of the pair.
+    pub second: usize,
+    /// The horizontal adjustment between the pair of characters, in pixels.
+    pub amount: isize,
+}
+
+/// The `BmFontFormat` parameter selects the variant of the BMFont descriptor to write.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum BmFontFormat {
+    /// The text variant, with one tag per line.
+    Text,
+    /// The XML variant.
+    Xml,
+}
+
+/// A `BmFont` is the contents of an AngelCode BMFont descriptor file. The page images
+/// are stored in separate files named by the descriptor.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BmFont {
+    /// The rasterization parameters of the font.
+    pub info: BmFontInfo,
+    /// The line metrics of the font.
+    pub common: BmFontCommon,
+    /// The file names of the page images, indexed by page.
+    pub pages: Vec<String>,
+    /// The characters of the font.
+    pub chars: Vec<BmFontChar>,
+    /// The kerning pairs of the font.
+    pub kernings: Vec<BmFontKerning>,
+}
+
+/// The largest number of pages a descriptor may have. The binary variant stores the page of
+/// a character in a single byte, so no BMFont descriptor has more pages than this.
+const MAX_PAGES: usize = 256;
+
+fn corrupt_descriptor(message: &str) -> Error {
+    Error::new(ErrorKind::CannotLoadBmFont, String::from(message).into())
+}
+
+/// A tag of a text or XML descriptor along with its attributes.
+struct Tag {
+    name: String,
+    attributes: HashMap<String, String>,
+}
+
+impl Tag {
+    /// Parse the attribute `key` of the tag.
+    fn attribute<T: FromStr>(&self, key: &str) -> Result<T, Error> {
+        let value = self.attributes.get(key).ok_or_else(|| {
+            corrupt_descriptor(&format!("the {} tag has no {} attribute", self.name, key))
+        })?;
+
+        value.trim().parse::<T>().map_err(|_| {
+            corrupt_descriptor(&format!("the {} attribute of the {} tag is invalid", key, self.name))
+        })
+    }
+
+    /// Parse the attribute `key` of the tag, or return `default` when the tag does not
+    /// have it.
+    fn attribute_or<T: FromStr>(&self, key: &str, default: T) -> Result<T, Error> {
+        if self.attributes.contains_key(key) {
+            self.attribute(key)
+        } else {
+            Ok(default)
+        }
+    }
+
+    /// Parse a comma separated list of numbers, such as the padding of the `info` tag.
+    fn list_attribute<const N: usize>(&self, key: &str) -> Result<[usize; N], Error> {
+        let mut list = [0; N];
+        let value = match self.attributes.get(key) {
+            Some(value) => value,
+            None => return Ok(list),
+        };
+        let items: Vec<&str> = value.split(',').collect();
+        if items.len() != N {
+            return Err(corrupt_descriptor(&format!("the {} attribute of the {} tag is invalid", key, self.name)));
+        }
+        for (item, entry) in items.iter().zip(list.iter_mut()) {
+            *entry = item.trim().parse().map_err(|_| {
+                corrupt_descriptor(&format!("the {} attribute of the {} tag is invalid", key, self.name))
+            })?;
+        }
+
+        Ok(list)
+    }
+}
+
+/// Parse a tag name followed by `key=value` attributes. Values may be quoted, in which
+/// case they can contain whitespace.
+fn parse_tag(source: &str, unescape: fn(&str) -> String) -> Option<Tag> {
+    let source = source.trim();
+    let name_end = source.find(char::is_whitespace).unwrap_or(source.len());
+    let name = &source[..name_end];
+    if name.is_empty() {
+        return None;
+    }
+
+    let mut attributes = HashMap::new();
+    let mut rest = source[name_end..].trim_start();
+    while !rest.is_empty() {
+        let key_end = rest.find(|ch: char| ch == '=' || ch.is_whitespace()).unwrap_or(rest.len());
+        let key = &rest[..key_end];
+        rest = rest[key_end..].trim_start();
+        if !rest.starts_with('=') {
+            // An attribute without a value carries no information.
+            continue;
+        }
+        rest = rest[1..].trim_start();
+        let value = if let Some(quoted) = rest.strip_prefix('"') {
+            let value_end = quoted.find('"').unwrap_or(quoted.len());
+            rest = quoted.get((value_end + 1)..).unwrap_or("");
+            &quoted[..value_end]
+        } else {
+            let value_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            let value = &rest[..value_end];
+            rest = &rest[value_end..];
+            value
+        };
+        attributes.insert(String::from(key), unescape(value));
+        rest = rest.trim_start();
+    }
+
+    Some(Tag { name: String::from(name), attributes: attributes })
+}
+
+/// Replace the predefined XML entities in an attribute value.
+fn unescape_xml(value: &str) -> String {
+    value
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+/// Split a text descriptor into its tags, one per line.
+fn text_tags(source: &str) -> Vec<Tag> {
+    source.lines()
+        .filter_map(|line| parse_tag(line, str::to_owned))
+        .collect()
+}
+
+/// Split an XML descriptor into its tags. The nesting of the elements carries no
+/// information beyond the tag names, so the document is read as a flat list of opening tags.
+fn xml_tags(source: &str) -> Result<Vec<Tag>, Error> {
+    let mut tags = vec![];
+    let mut rest = source;
+    while let Some(start) = rest.find('<') {
+        rest = &rest[(start + 1)..];
+        if let Some(comment) = rest.strip_prefix("!--") {
+            let end = comment.find("-->").ok_or_else(|| corrupt_descriptor("unterminated XML comment"))?;
+            rest = &comment[(end + 3)..];
+            continue;
+        }
+
+        // Find the end of the tag, skipping over quoted attribute values.
+        let mut in_quotes = false;
+        let end = rest.char_indices()
+            .find(|&(_, ch)| {
+                if ch == '"' {
+                    in_quotes = !in_quotes;
+                }
+                ch == '>' && !in_quotes
+            })
+            .map(|(end, _)| end)
+            .ok_or_else(|| corrupt_descriptor("unterminated XML tag"))?;
+        let tag = &rest[..end];
+        rest = &rest[(end + 1)..];
+        if tag.starts_with('?') || tag.starts_with('!') || tag.starts_with('/') {
+            continue;
+        }
+        let tag = tag.strip_suffix('/').unwrap_or(tag);
+        if let Some(tag) = parse_tag(tag, unescape_xml) {
+            tags.push(tag);
+        }
+    }
+
+    Ok(tags)
+}
+
+/// Assemble a descriptor from the tags of a text or XML descriptor.
+fn from_tags(tags: &[Tag]) -> Result<BmFont, Error> {
+    let mut info = None;
+    let mut common = None;
+    let mut page_count = MAX_PAGES;
+    let mut pages = vec![];
+    let mut chars = vec![];
+    let mut kernings = vec![];
+    for tag in tags.iter() {
+        match tag.name.as_str() {
+            "info" => {
+                let mut font_info = BmFontInfo::new(&tag.attribute_or("face", String::new())?, tag.attribute("size")?);
+                font_info.padding = tag.list_attribute("padding")?;
+                font_info.spacing = tag.list_attribute("spacing")?;
+                info = Some(font_info);
+            }
+            "common" => {
+                common = Some(BmFontCommon::new(
+                    tag.attribute("lineHeight")?,
+                    tag.attribute("base")?,
+                    tag.attribute("scaleW")?,
+                    tag.attribute("scaleH")?
+                ));
+                page_count = usize::min(tag.attribute_or("pages", MAX_PAGES)?, MAX_PAGES);
+            }
+            "page" => {
+                let id: usize = tag.attribute("id")?;
+                let file: String = tag.attribute("file")?;
+                if id >= page_count {
+                    return Err(corrupt_descriptor(&format!("the page id {} is out of range", id)));
+                }
+                if id >= pages.len() {
+                    pages.resize(id + 1, None);
+                }
+                pages[id] = Some(file);
+            }
+            "char" => {
+                // BMFont writes the glyph it draws for missing characters with the id -1,
+                // which has no code point to store it under.
+                let id: i64 = tag.attribute("id")?;
+                if id == -1 {
+                    continue;
+                }
+                let id = usize::try_from(id).map_err(|_| corrupt_descriptor("the id attribute of the char tag is invalid"))?;
+                chars.push(BmFontChar {
+                    id: id,
+                    x: tag.attribute("x")?,
+                    y: tag.attribute("y")?,
+                    width: tag.attribute("width")?,
+                    height: tag.attribute("height")?,
+                    x_offset: tag.attribute("xoffset")?,
+                    y_offset: tag.attribute("yoffset")?,
+                    x_advance: tag.attribute("xadvance")?,
+                    page: tag.attribute_or("page", 0)?,
+                });
+            }
+            "kerning" => {
+                kernings.push(BmFontKerning {
+                    first: tag.attribute("first")?,
+                    second: tag.attribute("second")?,
+                    amount: tag.attribute("amount")?,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    let common = common.ok_or_else(|| corrupt_descriptor("the descriptor has no common tag"))?;
+    let info = info.unwrap_or_else(|| BmFontInfo::new("", common.line_height as isize));
+    let pages = pages.into_iter().collect::<Option<Vec<String>>>().ok_or_else(|| {
+        corrupt_descriptor("the page ids of the descriptor are not contiguous")
+    })?;
+
+    Ok(BmFont {
+        info: info,
+        common: common,
+        pages: pages,
+        chars: chars,
+        kernings: kernings,
+    })
+}
+
+/// A cursor over the little endian fields of a binary descriptor.
+struct BinaryReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> BinaryReader<'a> {
+    fn bytes(&mut self, count: usize) -> Result<&'a [u8], Error> {
+        if self.data.len() < count {
+            return Err(corrupt_descriptor("the binary descriptor is truncated"));
+        }
+        let (bytes, rest) = self.data.split_at(count);
+        self.data = rest;
+
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, Error> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, Error> {
+        let bytes = self.bytes(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn i16(&mut self) -> Result<i16, Error> {
+        let bytes = self.bytes(2)?;
+        Ok(i16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, Error> {
+        let bytes = self.bytes(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    /// Read a null terminated string.
+    fn string(&mut self) -> Result<String, Error> {
+        let end = self.data.iter().position(|&byte| byte == 0).ok_or_else(|| {
+            corrupt_descriptor("the binary descriptor contains an unterminated string")
+        })?;
+        let string = String::from_utf8_lossy(&self.data[..end]).into_owned();
+        self.data = &self.data[(end + 1)..];
+
+        Ok(string)
+    }
+}
+
+/// Parse a version 3 binary descriptor.
+fn parse_binary(data: &[u8]) -> Result<BmFont, Error> {
+    let mut reader = BinaryReader { data: data };
+    if reader.bytes(3)? != b"BMF" {
+        return Err(corrupt_descriptor("the binary descriptor has no BMF signature"));
+    }
+    let version = reader.u8()?;
+    if version != 3 {
+        return Err(corrupt_descriptor(&format!("binary descriptor version {} is not supported", version)));
+    }
+
+    let mut info = None;
+    let mut common = None;
+    let mut pages = vec![];
+    let mut chars = vec![];
+    let mut kernings = vec![];
+    while !reader.data.is_empty() {
+        let block_type = reader.u8()?;
+        let block_size = reader.u32()? as usize;
+        let mut block = BinaryReader { data: reader.bytes(block_size)? };
+        match block_type {
+            1 => {
+                let size = block.i16()?;
+                // Skip the flags, character set, vertical stretch and supersampling level.
+                block.bytes(5)?;
+                let padding = [block.u8()?, block.u8()?, block.u8()?, block.u8()?];
+                let spacing = [block.u8()?, block.u8()?];
+                // Skip the outline thickness.
+                block.u8()?;
+                let face = block.string()?;
+                let mut font_info = BmFontInfo::new(&face, size as isize);
+                font_info.padding = padding.map(|value| value as usize);
+                font_info.spacing = spacing.map(|value| value as usize);
+                info = Some(font_info);
+            }
+            2 => {
+                let line_height = block.u16()?;
+                let base = block.u16()?;
+                let scale_width = block.u16()?;
+                let scale_height = block.u16()?;
+                common = Some(BmFontCommon::new(
+                    line_height as usize, base as usize, scale_width as usize, scale_height as usize
+                ));
+            }
+            3 => {
+                while !block.data.is_empty() {
+                    pages.push(block.string()?);
+                }
+            }
+            4 => {
+                while !block.data.is_empty() {
+                    let ch = BmFontChar {
+                        id: block.u32()? as usize,
+                        x: block.u16()? as usize,
+                        y: block.u16()? as usize,
+                        width: block.u16()? as usize,
+                        height: block.u16()? as usize,
+                        x_offset: block.i16()? as isize,
+                        y_offset: block.i16()? as isize,
+                        x_advance: block.i16()? as isize,
+                        page: block.u8()? as usize,
+                    };
+                    // Skip the texture channel.
+                    block.u8()?;
+                    // The glyph for missing characters has the id -1 stored as an unsigned integer.
+                    if ch.id != u32::MAX as usize {
+                        chars.push(ch);
+                    }
+                }
+            }
+            5 => {
+                while !block.data.is_empty() {
+                    kernings.push(BmFontKerning {
+                        first: block.u32()? as usize,
+                        second: block.u32()? as usize,
+                        amount: block.i16()? as isize,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let common = common.ok_or_else(|| corrupt_descriptor("the descriptor has no common block"))?;
+    let info = info.unwrap_or_else(|| BmFontInfo::new("", common.line_height as isize));
+
+    Ok(BmFont {
+        info: info,
+        common: common,
+        pages: pages,
+        chars: chars,
+        kernings: kernings,
+    })
+}
+
+/// Parse an AngelCode BMFont descriptor. The text, XML and binary variants of the format
+/// are all recognized from the contents of the descriptor.
+pub fn parse(data: &[u8]) -> Result<BmFont, Error> {
+    if data.starts_with(b"BMF") {
+        return parse_binary(data);
+    }
+
+    let source = std::str::from_utf8(data).map_err(|e| {
+        Error::new(ErrorKind::CannotLoadBmFont, Box::new(e))
+    })?;
+    let source = source.trim_start_matches('\u{feff}').trim_start();
+    if source.starts_with('<') {
+        from_tags(&xml_tags(source)?)
+    } else {
+        from_tags(&text_tags(source))
+    }
+}
+
+/// Convert a BMFont descriptor and its page images into a bitmap font atlas.
+///
+/// The characters become the glyphs of a packed atlas whose glyph size is the font size
+/// of the descriptor, with no padding. The glyph metrics are normalized by the font size,
+/// the ascent of the atlas is the distance from the top of a line to the baseline, and the
+/// descent makes up the rest of the line height. The pages must be stored with a top left
+/// origin, which becomes the origin of the atlas.
+pub fn from_bmfont(font: &BmFont, pages: Vec<BitmapFontAtlasImage>) -> Result<BitmapFontAtlas, Error> {
+    let common = &font.common;
+    let (width, height) = (common.scale_width, common.scale_height);
+    if pages.is_empty() {
+        return Err(Error::new(ErrorKind::FontAtlasImageNotFound, "the font has no pages".into()));
+    }
+    for page in pages.iter() {
+        if page.width != width || page.height != height || page.origin != Origin::TopLeft {
+            return Err(Error::new(
+                ErrorKind::CannotLoadAtlasImage,
+                format!("the font pages must be {}x{} pixels with a top left origin", width, height).into()
+            ));
+        }
+    }
+
+    let glyph_size = match font.info.size.unsigned_abs() {
+        0 => common.line_height,
+        size => size,
+    };
+    if glyph_size == 0 {
+        return Err(corrupt_descriptor("the font size is zero"));
+    }
+    let slot = glyph_size as f32;
+
+    let mut glyph_metadata = HashMap::new();
+    for ch in font.chars.iter() {
+        if ch.page >= pages.len() {
+            return Err(corrupt_descriptor(&format!("character {} is stored on a missing page", ch.id)));
+        }
+        let inside = ch.x.checked_add(ch.width).is_some_and(|right| right <= width)
+            && ch.y.checked_add(ch.height).is_some_and(|bottom| bottom <= height);
+        if !inside {
+            return Err(corrupt_descriptor(&format!("character {} lies outside of its page", ch.id)));
+        }
+
+        let (glyph_width, glyph_height) = if ch.width == 0 || ch.height == 0 {
+            (f32::min(ch.x_advance as f32 / slot, 1.0), 0.0)
+        } else {
+            (ch.width as f32 / slot, ch.height as f32 / slot)
+        };
+        let y_offset = isize::try_from(ch.height).ok()
+            .and_then(|height| ch.y_offset.checked_add(height))
+            .and_then(|bottom| isize::try_from(common.base).ok()?.checked_sub(bottom))
+            .ok_or_else(|| corrupt_descriptor(&format!("character {} has an out of range offset", ch.id)))?;
+        let y_offset = y_offset as f32 / slot;
+        let x_min = ch.x as f32 / width as f32;
+        let y_min = ch.y as f32 / height as f32;
+        let mut metadata = GlyphMetadata::new(
+            ch.id, 0, 0, glyph_width, glyph_height, x_min, y_min, y_offset
+        );
+        metadata.x_offset = ch.x_offset as f32 / slot;
+        metadata.advance = ch.x_advance as f32 / slot;
+        metadata.page = ch.page;
+        metadata.rect = Some(GlyphRect::new(ch.x, ch.y, ch.width, ch.height));
+        glyph_metadata.insert(ch.id, metadata);
+    }
+
+    let kerning = font.kernings.iter()
+        .map(|pair| ((pair.first, pair.second), pair.amount as f32 / slot))
+        .collect();
+    let metadata = BitmapFontAtlasMetadata {
+        origin: Origin::TopLeft,
+        layout: AtlasLayout::Packed,
+        width: width,
+        height: height,
+        columns: 0,
+        rows: 0,
+        padding: 0,
+        slot_glyph_size: glyph_size,
+        glyph_size: glyph_size,
+        font_size: slot,
+        ascent: common.base as f32 / slot,
+        descent: (common.base as f32 - common.line_height as f32) / slot,
+        line_gap: 0.0,
+        page_count: pages.len(),
+        glyph_metadata: glyph_metadata,
+        kerning: kerning,
+        distance_field: None,
+        notdef: None,
+    };
+
+    Ok(BitmapFontAtlas::new(metadata, pages))
+}
+
+/// Load one page image of a BMFont. Grayscale pages store the glyph coverage in a single
+/// channel and stay single channel. Other pages without an alpha channel store the glyph
+/// coverage in their color channels, so the alpha channel is taken from the luminance.
+fn load_page<P: AsRef<Path>>(path: P) -> Result<BitmapFontAtlasImage, Error> {
+    let image = image::open(&path).map_err(|e| match e {
+        image::ImageError::IoError(e) => Error::new(ErrorKind::FontAtlasImageNotFound, Box::new(e)),
+        e => Error::new(ErrorKind::CannotLoadAtlasImage, Box::new(e)),
+    })?;
+    let (width, height) = (image.width() as usize, image.height() as usize);
+    if let image::DynamicImage::ImageLuma8(luma) = image {
+        return Ok(BitmapFontAtlasImage::new(luma.into_raw(), width, height, PixelFormat::R8, Origin::TopLeft));
+    }
+
+    let has_alpha = image.color().has_alpha();
+    let mut rgba = image.to_rgba8();
+    if !has_alpha {
+        let luma = image.to_luma8();
+        for (pixel, value) in rgba.pixels_mut().zip(luma.pixels()) {
+            pixel[3] = value[0];
+        }
+    }
+
+    Ok(BitmapFontAtlasImage::new(rgba.into_raw(), width, height, PixelFormat::Rgba8, Origin::TopLeft))
+}
+
+/// Load an AngelCode BMFont descriptor and its page images as a bitmap font atlas. The
+/// page file names are resolved relative to the directory containing the descriptor.
+pub fn load<P: AsRef<Path>>(path: P) -> Result<BitmapFontAtlas, Error> {
+    let data = fs::read(&path).map_err(|e| {
+        Error::new(ErrorKind::FileNotFound, Box::new(e))
+    })?;
+    let font = parse(&data)?;
+    let directory = path.as_ref().parent().unwrap_or_else(|| Path::new(""));
+    let mut pages = vec![];
+    for file_name in font.pages.iter() {
+        pages.push(load_page(directory.join(file_name))?);
+    }
+
+    from_bmfont(&font, pages)
+}
+
+/// Convert a bitmap font atlas into a BMFont descriptor. The page images are named
+/// `<name>_<page>.png`.
+///
+/// The normalized glyph metrics are scaled by the slot glyph size and rounded to whole
+/// pixels. Every glyph becomes a character covering its pixel rectangle on its page, and
+/// the baseline sits at the ascent of the atlas below the top of the line.
+pub fn to_bmfont(atlas: &BitmapFontAtlas, name: &str) -> BmFont {
+    let slot = atlas.slot_glyph_size as f32;
+    // The baseline of a descriptor cannot lie above the top of the line, so a negative
+    // ascent puts it at the top, and the characters are placed relative to that baseline.
+    let base = isize::max(f32::round(atlas.ascent * slot) as isize, 0);
+    let line_height = f32::round(atlas.line_height() * slot) as usize;
+
+    let mut glyphs: Vec<&GlyphMetadata> = atlas.glyph_metadata.values().collect();
+    glyphs.sort_by_key(|glyph| glyph.code_point);
+    let chars = glyphs.iter()
+        .map(|glyph| {
+            let region = atlas.glyph_region(glyph);
+            let (width, height) = if region.width == 0 || region.height == 0 {
+                (0, 0)
+            } else {
+                (region.width, region.height)
+            };
+            let bottom = f32::round(glyph.y_offset * slot) as isize;
+            BmFontChar {
+                id: glyph.code_point,
+                x: region.x,
+                y: region.y,
+                width: width,
+                height: height,
+                x_offset: f32::round(glyph.x_offset * slot) as isize,
+                y_offset: base - bottom - height as isize,
+                x_advance: f32::round(glyph.advance * slot) as isize,
+                page: glyph.page,
+            }
+        })
+        .collect();
+
+    let mut kernings: Vec<BmFontKerning> = atlas.kerning.iter()
+        .map(|(&(first, second), &amount)| BmFontKerning {
+            first: first,
+            second: second,
+            amount: f32::round(amount * slot) as isize,
+        })
+        .filter(|pair| pair.amount != 0)
+        .collect();
+    kernings.sort_by_key(|pair| (pair.first, pair.second));
+
+    BmFont {
+        info: BmFontInfo::new(name, f32::round(atlas.font_size) as isize),
+        common: BmFontCommon::new(line_height, base as usize, atlas.width, atlas.height),
+        pages: (0..atlas.pages.len()).map(|page| format!("{}_{}.png", name, page)).collect(),
+        chars: chars,
+        kernings: kernings,
+    }
+}
+
+/// Write the text variant of a BMFont descriptor. The text variant cannot escape quotes or
+/// line breaks in quoted values, so they are replaced with apostrophes and spaces in the
+/// face name, and writing fails when a page file name contains them.
+pub fn write_text<W: io::Write>(mut writer: W, font: &BmFont) -> io::Result<()> {
+    let info = &font.info;
+    let common = &font.common;
+    let is_unquotable = |ch: char| ch == '"' || ch == '\n' || ch == '\r';
+    if let Some(file) = font.pages.iter().find(|file| file.contains(is_unquotable)) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("the page file name {:?} cannot be stored in a text descriptor", file)
+        ));
+    }
+    let face: String = info.face.chars()
+        .map(|ch| match ch {
+            '"' => '\'',
+            '\n' | '\r' => ' ',
+            _ => ch,
+        })
+        .collect();
+    writeln!(
+        writer,
+        "info face=\"{}\" size={} bold=0 italic=0 charset=\"\" unicode=1 stretchH=100 smooth=1 aa=1 \
+        padding={},{},{},{} spacing={},{}",
+        face, info.size,
+        info.padding[0], info.padding[1], info.padding[2], info.padding[3],
+        info.spacing[0], info.spacing[1]
+    )?;
+    writeln!(
+        writer,
+        "common lineHeight={} base={} scaleW={} scaleH={} pages={} packed=0",
+        common.line_height, common.base, common.scale_width, common.scale_height, font.pages.len()
+    )?;
+    for (id, file) in font.pages.iter().enumerate() {
+        writeln!(writer, "page id={} file=\"{}\"", id, file)?;
+    }
+    writeln!(writer, "chars count={}", font.chars.len())?;
+    for ch in font.chars.iter() {
+        writeln!(
+            writer,
+            "char id={} x={} y={} width={} height={} xoffset={} yoffset={} xadvance={} page={} chnl=15",
+            ch.id, ch.x, ch.y, ch.width, ch.height, ch.x_offset, ch.y_offset, ch.x_advance, ch.page
+        )?;
+    }
+    if !font.kernings.is_empty() {
+        writeln!(writer, "kernings count={}", font.kernings.len())?;
+        for pair in font.kernings.iter() {
+            writeln!(writer, "kerning first={} second={} amount={}", pair.first, pair.second, pair.amount)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Replace the characters that cannot appear in an XML attribute value with entities.
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Write the XML variant of a BMFont descriptor.
+pub fn write_xml<W: io::Write>(mut writer: W, font: &BmFont) -> io::Result<()> {
+    let info = &font.info;
+    let common = &font.common;
+    writeln!(writer, "<?xml version=\"1.0\"?>")?;
+    writeln!(writer, "<font>")?;
+    writeln!(
+        writer,
+        "  <info face=\"{}\" size=\"{}\" bold=\"0\" italic=\"0\" charset=\"\" unicode=\"1\" stretchH=\"100\" \
+        smooth=\"1\" aa=\"1\" padding=\"{},{},{},{}\" spacing=\"{},{}\"/>",
+        escape_xml(&info.face), info.size,
+        info.padding[0], info.padding[1], info.padding[2], info.padding[3],
+        info.spacing[0], info.spacing[1]
+    )?;
+    writeln!(
+        writer,
+        "  <common lineHeight=\"{}\" base=\"{}\" scaleW=\"{}\" scaleH=\"{}\" pages=\"{}\" packed=\"0\"/>",
+        common.line_height, common.base, common.scale_width, common.scale_height, font.pages.len()
+    )?;
+    writeln!(writer, "  <pages>")?;
+    for (id, file) in font.pages.iter().enumerate() {
+        writeln!(writer, "    <page id=\"{}\" file=\"{}\" />", id, escape_xml(file))?;
+    }
+    writeln!(writer, "  </pages>")?;
+    writeln!(writer, "  <chars count=\"{}\">", font.chars.len())?;
+    for ch in font.chars.iter() {
+        writeln!(
+            writer,
+            "    <char id=\"{}\" x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" xoffset=\"{}\" yoffset=\"{}\" \
+            xadvance=\"{}\" page=\"{}\" chnl=\"15\" />",
+            ch.id, ch.x, ch.y, ch.width, ch.height, ch.x_offset, ch.y_offset, ch.x_advance, ch.page
+        )?;
+    }
+    writeln!(writer, "  </chars>")?;
+    if !font.kernings.is_empty() {
+        writeln!(writer, "  <kernings count=\"{}\">", font.kernings.len())?;
+        for pair in font.kernings.iter() {
+            writeln!(
+                writer,
+                "    <kerning first=\"{}\" second=\"{}\" amount=\"{}\" />",
+                pair.first, pair.second, pair.amount
+            )?;
+        }
+        writeln!(writer, "  </kernings>")?;
+    }
+    writeln!(writer, "</font>")?;
+
+    Ok(())
+}
+
+/// Create a file for writing.
+fn create_file(path: &Path) -> Result<io::BufWriter<File>, Error> {
+    let file = File::create(path).map_err(|e| {
+        Error::new(ErrorKind::CannotCreateFile, Box::new(e))
+    })?;
+
+    Ok(io::BufWriter::new(file))
+}
+
+/// Write a bitmap font atlas as a BMFont descriptor with the `.fnt` extension. The page
+/// images are written next to the descriptor as top down PNG files named after it.
+pub fn write_to_file<P: AsRef<Path>>(path: P, atlas: &BitmapFontAtlas, format: BmFontFormat) -> Result<(), Error> {
+    crate::check_page_dimensions(atlas)?;
+
+    let mut file_path = path.as_ref().to_path_buf();
+    file_path.set_extension("fnt");
+    let name = file_path.file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let directory = file_path.parent().unwrap_or_else(|| Path::new(""));
+    let font = to_bmfont(atlas, &name);
+
+    for (atlas_image, file_name) in atlas.pages.iter().zip(font.pages.iter()) {
+        let mut page_writer = create_file(&directory.join(file_name))?;
+        crate::encode_page(png::PngEncoder::new(&mut page_writer), atlas_image)?;
+        page_writer.flush().map_err(|e| {
+            Error::new(ErrorKind::CannotEncodeAtlasImage, Box::new(e))
+        })?;
+    }
+
+    let mut writer = create_file(&file_path)?;
+    let result = match format {
+        BmFontFormat::Text => write_text(&mut writer, &font),
+        BmFontFormat::Xml => write_xml(&mut writer, &font),
+    };
+
+    result.and_then(|_| writer.flush()).map_err(|e| {
+        Error::new(ErrorKind::CannotWriteMetadata, Box::new(e))
+    })
+}
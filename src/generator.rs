@@ -0,0 +1,581 @@
+use crate::{
+    AtlasLayout,
+    BitmapFontAtlas,
+    BitmapFontAtlasImage,
+    BitmapFontAtlasMetadata,
+    DistanceField,
+    Error,
+    ErrorKind,
+    GlyphMetadata,
+    GlyphRect,
+    Origin,
+    PixelFormat,
+};
+use crate::packing::SkylinePacker;
+use ab_glyph::{Font, FontRef, GlyphId, PxScale, ScaleFont};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+
+/// The `GeneratorSettings` struct holds the parameters that control how a TrueType or
+/// OpenType font gets rasterized into a bitmap font atlas.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GeneratorSettings {
+    /// The origin of the generated atlas image.
+    pub origin: Origin,
+    /// The arrangement of the glyphs in the generated atlas image.
+    pub layout: AtlasLayout,
+    /// The size of a glyph slot in the atlas in pixels.
+    pub slot_glyph_size: usize,
+    /// The number of pixels of padding from the edges of a glyph slot.
+    pub padding: usize,
+    /// The maximum width and height of an atlas page, in pixels. Glyphs that do not fit
+    /// on one page are stored on additional pages.
+    pub max_page_size: usize,
+    /// The unicode code points to rasterize into the atlas. Code points that the font
+    /// does not contain a glyph for are skipped.
+    pub code_points: Vec<usize>,
+    /// Whether to record the kerning between every pair of glyphs in the atlas. This is
+    /// off by default, since computing the table queries the font for every ordered pair
+    /// of glyphs, taking time proportional to the square of the number of glyphs. Only the
+    /// pairs with nonzero kerning end up in the metadata, so its size depends on the font.
+    pub kerning: bool,
+    /// The pixel format of the generated atlas images. The glyph coverage is written to
+    /// every channel of the format.
+    pub format: PixelFormat,
+    /// When set, the generator writes signed distance fields with the given range into
+    /// the atlas image instead of glyph coverage.
+    pub distance_field: Option<DistanceField>,
+}
+
+impl GeneratorSettings {
+    /// Construct generator settings for the printable ASCII characters.
+    pub fn new(slot_glyph_size: usize, padding: usize) -> GeneratorSettings {
+        GeneratorSettings {
+            origin: Origin::BottomLeft,
+            layout: AtlasLayout::Grid,
+            slot_glyph_size: slot_glyph_size,
+            padding: padding,
+            max_page_size: 4096,
+            code_points: (32..127).collect(),
+            kerning: false,
+            format: PixelFormat::Rgba8,
+            distance_field: None,
+        }
+    }
+}
+
+impl Default for GeneratorSettings {
+    fn default() -> GeneratorSettings {
+        GeneratorSettings::new(64, 3)
+    }
+}
+
+/// A `RasterizedGlyph` is a single glyph coverage bitmap along with the pixel
+/// metrics needed to place it in the atlas.
+struct RasterizedGlyph {
+    code_point: usize,
+    /// The width of the coverage bitmap, in pixels.
+    width: usize,
+    /// The height of the coverage bitmap, in pixels.
+    height: usize,
+    /// The coverage values, or the distance values for a distance field, of the glyph
+    /// stored row by row from the top of the glyph.
+    coverage: Vec<u8>,
+    /// The distance from the baseline to the bottom of the glyph bitmap, in pixels. This
+    /// is positive for glyphs that descend below the baseline.
+    bottom: f32,
+    /// The distance from the pen position to the left edge of the glyph bitmap, in pixels.
+    /// This is the left side bearing of the glyph, rounded to the pixel grid.
+    left: f32,
+    /// The horizontal advance of the glyph, in pixels.
+    advance: f32,
+}
+
+fn invalid_parameters(message: &str) -> Error {
+    Error::new(ErrorKind::InvalidAtlasParameters, message.into())
+}
+
+fn validate_settings(settings: &GeneratorSettings) -> Result<(), Error> {
+    if settings.slot_glyph_size == 0 {
+        return Err(invalid_parameters("the slot glyph size must be nonzero"));
+    }
+    if settings.padding >= settings.slot_glyph_size {
+        return Err(invalid_parameters("the padding must be smaller than the slot glyph size"));
+    }
+    if settings.max_page_size < settings.slot_glyph_size {
+        return Err(invalid_parameters("the maximum page size must hold at least one glyph slot"));
+    }
+    if let Some(distance_field) = settings.distance_field {
+        if !distance_field.range.is_finite() || distance_field.range <= 0.0 {
+            return Err(invalid_parameters("the distance field range must be positive and finite"));
+        }
+        // The distance field grows every glyph bitmap by the range on each side, which no
+        // scale can shrink.
+        let glyph_size = (settings.slot_glyph_size - settings.padding) as f32;
+        if 2.0 * f32::ceil(distance_field.range) >= glyph_size {
+            return Err(invalid_parameters("the distance field range leaves no room for the glyphs in a glyph slot"));
+        }
+    }
+
+    Ok(())
+}
+
+/// Convert a coverage bitmap into a signed distance field. The bitmap grows by the
+/// distance field range on every side so the distances outside the glyph fit.
+fn signed_distance_field(glyph: RasterizedGlyph, distance_field: DistanceField) -> RasterizedGlyph {
+    let range = distance_field.range;
+    let margin = f32::ceil(range) as usize;
+    let width = glyph.width + 2 * margin;
+    let height = glyph.height + 2 * margin;
+    let is_inside = |x: isize, y: isize| -> bool {
+        let (x, y) = (x - margin as isize, y - margin as isize);
+        if x < 0 || y < 0 || x >= glyph.width as isize || y >= glyph.height as isize {
+            false
+        } else {
+            glyph.coverage[y as usize * glyph.width + x as usize] >= 128
+        }
+    };
+
+    let search = margin as isize + 1;
+    let mut distances = vec![0; width * height];
+    for y in 0..(height as isize) {
+        for x in 0..(width as isize) {
+            let inside = is_inside(x, y);
+            let mut nearest_squared = f32::MAX;
+            for dy in -search..=search {
+                for dx in -search..=search {
+                    if is_inside(x + dx, y + dy) != inside {
+                        nearest_squared = f32::min(nearest_squared, (dx * dx + dy * dy) as f32);
+                    }
+                }
+            }
+
+            // The outline lies between the pixel centers on either side of it.
+            let distance = f32::min(f32::sqrt(nearest_squared) - 0.5, range);
+            let signed_distance = if inside { distance } else { -distance };
+            let value = f32::clamp(0.5 + signed_distance / (2.0 * range), 0.0, 1.0);
+            distances[y as usize * width + x as usize] = (value * 255.0).round() as u8;
+        }
+    }
+
+    RasterizedGlyph {
+        code_point: glyph.code_point,
+        width: width,
+        height: height,
+        coverage: distances,
+        bottom: glyph.bottom + margin as f32,
+        left: glyph.left - margin as f32,
+        advance: glyph.advance,
+    }
+}
+
+/// Rasterize each requested glyph at the given scale.
+fn rasterize_glyphs<F: Font>(font: &F, settings: &GeneratorSettings, scale: PxScale) -> Vec<RasterizedGlyph> {
+    let scaled_font = font.as_scaled(scale);
+    let mut glyphs = vec![];
+    for &code_point in settings.code_points.iter() {
+        let ch = match std::char::from_u32(code_point as u32) {
+            Some(ch) => ch,
+            None => continue,
+        };
+        let glyph_id = font.glyph_id(ch);
+        if glyph_id.0 == 0 {
+            continue;
+        }
+
+        let advance = scaled_font.h_advance(glyph_id);
+        let glyph = glyph_id.with_scale(scale);
+        let rasterized = match font.outline_glyph(glyph) {
+            Some(outlined) => {
+                let bounds = outlined.px_bounds();
+                let width = bounds.width() as usize;
+                let height = bounds.height() as usize;
+                let mut coverage = vec![0; width * height];
+                outlined.draw(|x, y, c| {
+                    let (x, y) = (x as usize, y as usize);
+                    if x < width && y < height {
+                        coverage[y * width + x] = (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+                    }
+                });
+
+                let rasterized = RasterizedGlyph {
+                    code_point: code_point,
+                    width: width,
+                    height: height,
+                    coverage: coverage,
+                    bottom: bounds.max.y,
+                    left: bounds.min.x,
+                    advance: advance,
+                };
+                match settings.distance_field {
+                    Some(distance_field) => signed_distance_field(rasterized, distance_field),
+                    None => rasterized,
+                }
+            }
+            None => RasterizedGlyph {
+                code_point: code_point,
+                width: 0,
+                height: 0,
+                coverage: vec![],
+                bottom: 0.0,
+                left: 0.0,
+                advance: advance,
+            },
+        };
+
+        glyphs.push(rasterized);
+    }
+
+    glyphs
+}
+
+/// Rasterize the glyphs so that every glyph bitmap fits inside a glyph slot. The font is
+/// scaled down from the glyph size until the largest glyph fits. This returns the glyphs
+/// along with the scale they were rasterized at.
+fn rasterize_fitted_glyphs<F: Font>(font: &F, settings: &GeneratorSettings) -> (Vec<RasterizedGlyph>, PxScale) {
+    let glyph_size = (settings.slot_glyph_size - settings.padding) as f32;
+    let mut scale = glyph_size;
+    loop {
+        let glyphs = rasterize_glyphs(font, settings, PxScale::from(scale));
+        let largest = glyphs.iter()
+            .map(|glyph| usize::max(glyph.width, glyph.height))
+            .max()
+            .unwrap_or(0) as f32;
+        if largest <= glyph_size || scale <= 1.0 {
+            return (glyphs, PxScale::from(scale));
+        }
+
+        scale = f32::max(1.0, f32::min(scale - 1.0, scale * glyph_size / largest));
+    }
+}
+
+/// Compute the kerning table between every pair of rasterized glyphs, normalized by the
+/// slot glyph size.
+fn kerning_table<F: Font>(
+    font: &F, glyphs: &[RasterizedGlyph], scale: PxScale, settings: &GeneratorSettings) -> HashMap<(usize, usize), f32> {
+
+    let scaled_font = font.as_scaled(scale);
+    let slot = settings.slot_glyph_size as f32;
+    let glyph_ids: Vec<(usize, GlyphId)> = glyphs.iter()
+        .filter_map(|glyph| {
+            std::char::from_u32(glyph.code_point as u32).map(|ch| (glyph.code_point, font.glyph_id(ch)))
+        })
+        .collect();
+
+    let mut kerning = HashMap::new();
+    for &(first, first_id) in glyph_ids.iter() {
+        for &(second, second_id) in glyph_ids.iter() {
+            let amount = scaled_font.kern(first_id, second_id);
+            if amount != 0.0 {
+                kerning.insert((first, second), amount / slot);
+            }
+        }
+    }
+
+    kerning
+}
+
+/// The `FontMetrics` struct holds the font wide metrics of the rasterized font, normalized
+/// by the slot glyph size.
+struct FontMetrics {
+    font_size: f32,
+    ascent: f32,
+    descent: f32,
+    line_gap: f32,
+    kerning: HashMap<(usize, usize), f32>,
+}
+
+/// Compute the font wide metrics at the scale the glyphs were rasterized at.
+fn font_metrics<F: Font>(
+    font: &F, glyphs: &[RasterizedGlyph], scale: PxScale, settings: &GeneratorSettings) -> FontMetrics {
+
+    let scaled_font = font.as_scaled(scale);
+    let slot = settings.slot_glyph_size as f32;
+    let kerning = if settings.kerning {
+        kerning_table(font, glyphs, scale, settings)
+    } else {
+        HashMap::new()
+    };
+
+    FontMetrics {
+        font_size: scale.y,
+        ascent: scaled_font.ascent() / slot,
+        descent: scaled_font.descent() / slot,
+        line_gap: scaled_font.line_gap() / slot,
+        kerning: kerning,
+    }
+}
+
+/// The `PlacedGlyph` struct records where a rasterized glyph ends up in the atlas.
+#[derive(Copy, Clone)]
+struct PlacedGlyph {
+    page: usize,
+    row: usize,
+    column: usize,
+    /// The pixel rectangle of the glyph including its padding, measured from the top
+    /// left corner of the atlas page.
+    rect: GlyphRect,
+}
+
+/// The `Placement` struct records the dimensions of the atlas pages along with where
+/// every glyph ends up in the atlas.
+struct Placement {
+    width: usize,
+    height: usize,
+    rows: usize,
+    columns: usize,
+    page_count: usize,
+    glyphs: Vec<PlacedGlyph>,
+}
+
+/// Return the size of the atlas region a glyph occupies, including its padding.
+fn region_size(glyph: &RasterizedGlyph, padding: usize) -> (usize, usize) {
+    if glyph.width == 0 || glyph.height == 0 {
+        (0, 0)
+    } else {
+        (glyph.width + padding, glyph.height + padding)
+    }
+}
+
+/// Place the glyphs in a square grid of glyph slots. When the glyphs do not fit in a
+/// single page, every page holds as many slots as the maximum page size allows.
+fn place_grid(glyphs: &[RasterizedGlyph], settings: &GeneratorSettings) -> Placement {
+    let slot_glyph_size = settings.slot_glyph_size;
+    let slots_per_side = settings.max_page_size / slot_glyph_size;
+    let (rows, columns) = if glyphs.len() <= slots_per_side * slots_per_side {
+        let columns = f32::ceil(f32::sqrt(glyphs.len() as f32)) as usize;
+        (glyphs.len().div_ceil(columns), columns)
+    } else {
+        (slots_per_side, slots_per_side)
+    };
+    let slots_per_page = rows * columns;
+    let placements = glyphs.iter().enumerate().map(|(i, glyph)| {
+        let page = i / slots_per_page;
+        let row = (i % slots_per_page) / columns;
+        let column = (i % slots_per_page) % columns;
+        let (width, height) = region_size(glyph, settings.padding);
+        let rect = GlyphRect::new(column * slot_glyph_size, row * slot_glyph_size, width, height);
+
+        PlacedGlyph { page: page, row: row, column: column, rect: rect }
+    });
+
+    Placement {
+        width: columns * slot_glyph_size,
+        height: rows * slot_glyph_size,
+        rows: rows,
+        columns: columns,
+        page_count: glyphs.len().div_ceil(slots_per_page),
+        glyphs: placements.collect(),
+    }
+}
+
+/// Pack the glyphs as tightly as possible into atlas pages whose side lengths are powers
+/// of two. The pages grow until they reach the maximum page size, after which the
+/// remaining glyphs spill over onto new pages.
+fn place_packed(glyphs: &[RasterizedGlyph], settings: &GeneratorSettings) -> Result<Placement, Error> {
+    let sizes: Vec<(usize, usize)> = glyphs.iter()
+        .map(|glyph| region_size(glyph, settings.padding))
+        .collect();
+    let area: usize = sizes.iter().map(|&(width, height)| width * height).sum();
+    let largest = sizes.iter().map(|&(width, height)| usize::max(width, height)).max().unwrap_or(0);
+    if largest > settings.max_page_size {
+        return Err(invalid_parameters("a glyph is larger than the maximum page size"));
+    }
+
+    // Pack the tallest glyphs first since the skyline wastes less space that way.
+    let mut order: Vec<usize> = (0..glyphs.len()).collect();
+    order.sort_by_key(|&i| (std::cmp::Reverse(sizes[i].1), std::cmp::Reverse(sizes[i].0)));
+
+    let mut size = usize::max(1, usize::max(largest, f32::sqrt(area as f32) as usize)).next_power_of_two();
+    size = usize::min(size, settings.max_page_size);
+    loop {
+        let allow_new_pages = size >= settings.max_page_size;
+        let mut packer = SkylinePacker::new(size, size);
+        let mut page = 0;
+        let mut placements = vec![PlacedGlyph { page: 0, row: 0, column: 0, rect: GlyphRect::new(0, 0, 0, 0) }; glyphs.len()];
+        let mut packed_all = true;
+        for &i in order.iter() {
+            let (width, height) = sizes[i];
+            let mut rect = packer.pack(width, height);
+            if rect.is_none() && allow_new_pages {
+                packer = SkylinePacker::new(size, size);
+                page += 1;
+                rect = packer.pack(width, height);
+            }
+            match rect {
+                Some(rect) => placements[i] = PlacedGlyph { page: page, row: 0, column: 0, rect: rect },
+                None => {
+                    packed_all = false;
+                    break;
+                }
+            }
+        }
+
+        if packed_all {
+            // The skyline fills the pages from the top, so trim the unused rows at the bottom.
+            let used_height = placements.iter().map(|placed| placed.rect.y + placed.rect.height).max().unwrap_or(0);
+            let height = usize::min(size, usize::max(1, used_height).next_power_of_two());
+
+            return Ok(Placement {
+                width: size,
+                height: height,
+                rows: 0,
+                columns: 0,
+                page_count: page + 1,
+                glyphs: placements,
+            });
+        }
+
+        size = usize::min(2 * size, settings.max_page_size);
+    }
+}
+
+/// Write a coverage value to every channel of a pixel, widening it to the channel size
+/// of the pixel format.
+fn write_coverage(pixel: &mut [u8], format: PixelFormat, value: u8) {
+    match format.bytes_per_channel() {
+        1 => pixel.fill(value),
+        _ => {
+            let value = (value as u16 * 257).to_ne_bytes();
+            for channel in pixel.chunks_exact_mut(2) {
+                channel.copy_from_slice(&value);
+            }
+        }
+    }
+}
+
+/// Assemble the atlas pages and glyph metadata from the rasterized glyphs and their
+/// positions in the atlas.
+fn assemble(
+    glyphs: Vec<RasterizedGlyph>,
+    placement: Placement,
+    font_metrics: FontMetrics,
+    settings: &GeneratorSettings) -> BitmapFontAtlas {
+
+    let (width, height) = (placement.width, placement.height);
+    let slot_glyph_size = settings.slot_glyph_size;
+    let padding = settings.padding;
+    let inset = padding / 2;
+
+    let bytes_per_pixel = settings.format.bytes_per_pixel();
+    let mut pages = vec![vec![0; bytes_per_pixel * width * height]; placement.page_count];
+    let mut glyph_metadata = HashMap::new();
+    for (glyph, placed) in glyphs.iter().zip(placement.glyphs.iter()) {
+        let rect = placed.rect;
+        let data = &mut pages[placed.page];
+        for y in 0..glyph.height {
+            for x in 0..glyph.width {
+                let value = glyph.coverage[y * glyph.width + x];
+                let offset = bytes_per_pixel * ((rect.y + inset + y) * width + rect.x + inset + x);
+                write_coverage(&mut data[offset..(offset + bytes_per_pixel)], settings.format, value);
+            }
+        }
+
+        let slot = slot_glyph_size as f32;
+        let (glyph_width, glyph_height) = if rect.width == 0 || rect.height == 0 {
+            (f32::min(glyph.advance / slot, 1.0), 0.0)
+        } else {
+            (rect.width as f32 / slot, rect.height as f32 / slot)
+        };
+        let x_offset = (glyph.left - inset as f32) / slot;
+        let y_offset = (-glyph.bottom - (padding - inset) as f32) / slot;
+        let (region_height, rect) = match settings.layout {
+            AtlasLayout::Grid => (slot_glyph_size, None),
+            AtlasLayout::Packed => (rect.height, Some(rect)),
+        };
+        let x_min = placed.rect.x as f32 / width as f32;
+        let y_min = match settings.origin {
+            Origin::TopLeft => placed.rect.y as f32 / height as f32,
+            Origin::BottomLeft => 1.0 - (placed.rect.y + region_height) as f32 / height as f32,
+        };
+        let mut metadata = GlyphMetadata::new(
+            glyph.code_point, placed.row, placed.column,
+            glyph_width, glyph_height, x_min, y_min, y_offset
+        );
+        metadata.x_offset = x_offset;
+        metadata.advance = glyph.advance / slot;
+        metadata.page = placed.page;
+        metadata.rect = rect;
+        glyph_metadata.insert(glyph.code_point, metadata);
+    }
+
+    let pages = pages.into_iter().map(|data| {
+        let mut image = BitmapFontAtlasImage::new(data, width, height, settings.format, Origin::TopLeft);
+        image.convert_origin(settings.origin);
+
+        image
+    });
+    let metadata = BitmapFontAtlasMetadata {
+        origin: settings.origin,
+        layout: settings.layout,
+        width: width,
+        height: height,
+        columns: placement.columns,
+        rows: placement.rows,
+        padding: padding,
+        slot_glyph_size: slot_glyph_size,
+        glyph_size: slot_glyph_size - padding,
+        font_size: font_metrics.font_size,
+        ascent: font_metrics.ascent,
+        descent: font_metrics.descent,
+        line_gap: font_metrics.line_gap,
+        page_count: placement.page_count,
+        glyph_metadata: glyph_metadata,
+        kerning: font_metrics.kerning,
+        distance_field: settings.distance_field,
+        // The .notdef glyph of the font has no code point, so it is never rasterized.
+        notdef: None,
+    };
+
+    BitmapFontAtlas::new(metadata, pages.collect())
+}
+
+/// Rasterize a TrueType or OpenType font stored in memory into a bitmap font atlas.
+///
+/// In a grid layout, each glyph is drawn into the top left corner of its glyph slot, inset
+/// by half the padding. In a packed layout, each glyph gets its own pixel rectangle that
+/// fits the glyph plus its padding. Glyphs that do not fit on a single page of the maximum
+/// page size spill over onto additional pages. The glyph metrics are normalized by the slot
+/// glyph size, the same way they are stored in a bmfa file. When the settings request a
+/// distance field, the atlas image stores signed distances to the glyph outlines instead
+/// of coverage.
+///
+/// Generated atlases have no `.notdef` glyph. The `.notdef` glyph of a font is glyph 0,
+/// which no code point maps to, so it has no key to be stored under in the glyph table,
+/// and code points the font maps to glyph 0 are skipped. Set `notdef` on the atlas to the
+/// code point of one of its glyphs to draw that glyph for missing characters instead.
+pub fn generate(font_data: &[u8], settings: &GeneratorSettings) -> Result<BitmapFontAtlas, Error> {
+    validate_settings(settings)?;
+    let font = FontRef::try_from_slice(font_data).map_err(|e| {
+        Error::new(ErrorKind::CannotLoadFont, Box::new(e))
+    })?;
+    let (glyphs, scale) = rasterize_fitted_glyphs(&font, settings);
+    if glyphs.is_empty() {
+        return Err(invalid_parameters("the font contains none of the requested glyphs"));
+    }
+    let glyph_size = settings.slot_glyph_size - settings.padding;
+    let fits = glyphs.iter().all(|glyph| glyph.width <= glyph_size && glyph.height <= glyph_size);
+    if settings.layout == AtlasLayout::Grid && !fits {
+        return Err(invalid_parameters("the glyphs do not fit in a glyph slot at any scale"));
+    }
+
+    let placement = match settings.layout {
+        AtlasLayout::Grid => place_grid(&glyphs, settings),
+        AtlasLayout::Packed => place_packed(&glyphs, settings)?,
+    };
+
+    let font_metrics = font_metrics(&font, &glyphs, scale, settings);
+
+    Ok(assemble(glyphs, placement, font_metrics, settings))
+}
+
+/// Rasterize a TrueType or OpenType font file into a bitmap font atlas.
+pub fn generate_from_file<P: AsRef<Path>>(path: P, settings: &GeneratorSettings) -> Result<BitmapFontAtlas, Error> {
+    let font_data = fs::read(&path).map_err(|e| {
+        Error::new(ErrorKind::FileNotFound, Box::new(e))
+    })?;
+
+    generate(&font_data, settings)
+}
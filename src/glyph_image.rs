@@ -0,0 +1,201 @@
+use crate::{BitmapFontAtlas, BitmapFontAtlasImage, GlyphBitmap, GlyphMetadata, GlyphRect, Origin, PixelFormat};
+
+
+/// The `GlyphBounds` parameter selects which part of the atlas page around a glyph to
+/// extract.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum GlyphBounds {
+    /// The whole glyph slot of a grid atlas, or the pixel rectangle of a packed atlas,
+    /// including any padding.
+    Slot,
+    /// The smallest rectangle inside the slot holding every pixel of the glyph with
+    /// nonzero coverage. The rectangle is empty for glyphs without any such pixel.
+    Tight,
+}
+
+/// A `GlyphImageView` is a view of the pixels of a single glyph inside its atlas page,
+/// without copying them. The view counts rows from the top of the glyph down regardless
+/// of the origin of the atlas.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GlyphImageView<'a> {
+    /// The atlas page the glyph is stored on.
+    image: &'a BitmapFontAtlasImage,
+    /// The pixel rectangle of the view, measured from the top left corner of the page as
+    /// it is stored in the PNG file.
+    rect: GlyphRect,
+}
+
+impl<'a> GlyphImageView<'a> {
+    /// Return the pixel rectangle of the view on its atlas page, measured from the top left
+    /// corner of the page as it is stored in the PNG file.
+    #[inline]
+    pub fn rect(&self) -> GlyphRect {
+        self.rect
+    }
+
+    /// Return the width of the view in pixels.
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.rect.width
+    }
+
+    /// Return the height of the view in pixels.
+    #[inline]
+    pub fn height(&self) -> usize {
+        self.rect.height
+    }
+
+    /// Return the pixel format of the view.
+    #[inline]
+    pub fn format(&self) -> PixelFormat {
+        self.image.format()
+    }
+
+    /// Return the bytes of row `y` of the view, counting rows from the top of the glyph.
+    pub fn row(&self, y: usize) -> &'a [u8] {
+        assert!(y < self.rect.height, "row {} is outside of a view {} pixels high", y, self.rect.height);
+        let page_y = self.rect.y + y;
+        let memory_y = match self.image.origin() {
+            Origin::TopLeft => page_y,
+            Origin::BottomLeft => self.image.height() - page_y - 1,
+        };
+        let bytes_per_pixel = self.format().bytes_per_pixel();
+        let offset = memory_y * self.image.width_in_bytes() + self.rect.x * bytes_per_pixel;
+
+        &self.image.as_ref()[offset..(offset + self.rect.width * bytes_per_pixel)]
+    }
+
+    /// Return the rows of the view from the top of the glyph down.
+    pub fn rows(&self) -> impl Iterator<Item = &'a [u8]> + '_ {
+        (0..self.rect.height).map(move |y| self.row(y))
+    }
+
+    /// Return the bytes of the pixel at column `x` and row `y` of the view, counting rows
+    /// from the top of the glyph.
+    pub fn pixel(&self, x: usize, y: usize) -> &'a [u8] {
+        assert!(x < self.rect.width, "column {} is outside of a view {} pixels wide", x, self.rect.width);
+        let bytes_per_pixel = self.format().bytes_per_pixel();
+
+        &self.row(y)[(x * bytes_per_pixel)..((x + 1) * bytes_per_pixel)]
+    }
+
+    /// Copy the pixels of the view into a buffer, row by row from the top of the glyph
+    /// down, without any space between the rows.
+    pub fn to_vec(&self) -> Vec<u8> {
+        self.rows().flat_map(|row| row.iter().cloned()).collect()
+    }
+
+    /// Return a view of a rectangle inside this view, given relative to this view.
+    fn sub_view(&self, x: usize, y: usize, width: usize, height: usize) -> GlyphImageView<'a> {
+        GlyphImageView {
+            image: self.image,
+            rect: GlyphRect::new(self.rect.x + x, self.rect.y + y, width, height),
+        }
+    }
+
+    /// Shrink the view by the padding around the glyph, which is drawn inset by half of the
+    /// padding from the top left corner of its slot.
+    fn without_padding(&self, padding: usize) -> GlyphImageView<'a> {
+        if self.rect.width < padding || self.rect.height < padding {
+            return self.sub_view(0, 0, 0, 0);
+        }
+        let inset = padding / 2;
+
+        self.sub_view(inset, inset, self.rect.width - padding, self.rect.height - padding)
+    }
+
+    /// Shrink the view to the smallest rectangle holding every pixel with nonzero coverage.
+    /// The coverage is the alpha channel for formats with alpha, and every channel otherwise.
+    fn tight(&self) -> GlyphImageView<'a> {
+        let format = self.format();
+        let bytes_per_pixel = format.bytes_per_pixel();
+        let coverage_bytes = match format.channel_count() {
+            2 | 4 => (bytes_per_pixel - format.bytes_per_channel())..bytes_per_pixel,
+            _ => 0..bytes_per_pixel,
+        };
+        let is_covered = |x: usize, y: usize| {
+            self.pixel(x, y)[coverage_bytes.clone()].iter().any(|&byte| byte != 0)
+        };
+
+        let (mut left, mut top, mut right, mut bottom) = (self.width(), self.height(), 0, 0);
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                if is_covered(x, y) {
+                    left = usize::min(left, x);
+                    top = usize::min(top, y);
+                    right = usize::max(right, x + 1);
+                    bottom = usize::max(bottom, y + 1);
+                }
+            }
+        }
+        if left >= right {
+            return self.sub_view(0, 0, 0, 0);
+        }
+
+        self.sub_view(left, top, right - left, bottom - top)
+    }
+}
+
+impl BitmapFontAtlas {
+    /// Return the pixel rectangle of the glyph slot of a glyph, measured from the top left
+    /// corner of the page as it is stored in the PNG file.
+    fn glyph_slot(&self, glyph: &GlyphMetadata) -> Option<GlyphRect> {
+        let rect = match glyph.rect {
+            Some(rect) => rect,
+            None => GlyphRect::new(
+                glyph.column.checked_mul(self.slot_glyph_size)?,
+                glyph.row.checked_mul(self.slot_glyph_size)?,
+                self.slot_glyph_size,
+                self.slot_glyph_size
+            ),
+        };
+
+        Some(rect)
+    }
+
+    /// Return a view of the pixels of the glyph for a code point, without copying them.
+    /// This is `None` when the atlas has no glyph for the code point, or the glyph does
+    /// not lie inside its page.
+    pub fn glyph_view(&self, code_point: usize, bounds: GlyphBounds) -> Option<GlyphImageView<'_>> {
+        let glyph = self.glyph_metadata.get(&code_point)?;
+        let image = self.pages.get(glyph.page)?;
+        let rect = self.glyph_slot(glyph)?;
+        let fits_x = rect.x.checked_add(rect.width).is_some_and(|right| right <= image.width());
+        let fits_y = rect.y.checked_add(rect.height).is_some_and(|bottom| bottom <= image.height());
+        if !fits_x || !fits_y {
+            return None;
+        }
+        let view = GlyphImageView { image: image, rect: rect };
+
+        match bounds {
+            GlyphBounds::Slot => Some(view),
+            GlyphBounds::Tight => Some(view.tight()),
+        }
+    }
+
+    /// Copy the pixels of the glyph for a code point into a glyph bitmap, along with the
+    /// offsets placing the copied rectangle on a line of text and the advance of the glyph,
+    /// in pixels. Unlike a view of the slot, the bitmap of the slot leaves out the padding
+    /// around the glyph, so that either bitmap can be handed back to a
+    /// [`crate::BitmapFontAtlasBuilder`] with the same padding to pack the glyph into
+    /// another atlas.
+    pub fn glyph_bitmap(&self, code_point: usize, bounds: GlyphBounds) -> Option<GlyphBitmap> {
+        let glyph = self.glyph_metadata.get(&code_point)?;
+        let view = match bounds {
+            GlyphBounds::Slot => self.glyph_view(code_point, bounds)?.without_padding(self.padding),
+            GlyphBounds::Tight => self.glyph_view(code_point, bounds)?,
+        };
+        let region = self.glyph_region(glyph);
+        let rect = view.rect();
+
+        // The offsets of the glyph locate the bottom left corner of its region, so shift
+        // them by the distance from that corner to the bottom left corner of the view.
+        let slot = self.slot_glyph_size as f32;
+        let mut bitmap = GlyphBitmap::new(view.to_vec(), rect.width, rect.height);
+        bitmap.x_offset = glyph.x_offset * slot + rect.x as f32 - region.x as f32;
+        bitmap.y_offset = glyph.y_offset * slot + (region.y + region.height) as f32 - (rect.y + rect.height) as f32;
+        bitmap.advance = glyph.advance * slot;
+
+        Some(bitmap)
+    }
+}
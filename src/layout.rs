@@ -0,0 +1,159 @@
+use crate::{BitmapFontAtlas, DEFAULT_FALLBACKS};
+
+
+/// A `Rect` is an axis aligned rectangle given by its minimum corner and its extent.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Rect {
+    /// The horizontal position of the minimum corner of the rectangle.
+    pub x: f32,
+    /// The vertical position of the minimum corner of the rectangle.
+    pub y: f32,
+    /// The width of the rectangle.
+    pub width: f32,
+    /// The height of the rectangle.
+    pub height: f32,
+}
+
+impl Rect {
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Rect {
+        Rect {
+            x: x,
+            y: y,
+            width: width,
+            height: height,
+        }
+    }
+}
+
+/// A `PositionedGlyph` is one glyph quad of laid out text.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PositionedGlyph {
+    /// The unicode code point of the glyph.
+    pub code_point: usize,
+    /// The atlas page the glyph is stored on.
+    pub page: usize,
+    /// The rectangle the glyph covers on the screen, in pixels. The screen coordinates have
+    /// the x-axis pointing right and the y-axis pointing down.
+    pub screen_rect: Rect,
+    /// The rectangle the glyph covers in the atlas page, in normalized texture coordinates.
+    /// The texture coordinates follow the origin of the atlas, so the minimum corner is the
+    /// top left corner of the glyph for a top left origin, and the bottom left corner of the
+    /// glyph for a bottom left origin.
+    pub uv_rect: Rect,
+}
+
+/// The `LayoutSettings` struct holds the parameters that control how text gets laid out.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LayoutSettings<'a> {
+    /// The font size, in pixels. Glyphs are scaled so that the font size the atlas was
+    /// rasterized at maps to this many pixels on the screen.
+    pub size: f32,
+    /// The horizontal position of the pen at the start of each line, in pixels.
+    pub x: f32,
+    /// The vertical position of the baseline of the first line, in pixels.
+    pub y: f32,
+    /// The distance between tab stops, in multiples of the width of a space.
+    pub tab_width: usize,
+    /// The characters tried in order for characters missing from the atlas, before the
+    /// `.notdef` glyph of the atlas.
+    pub fallbacks: &'a [char],
+}
+
+impl<'a> LayoutSettings<'a> {
+    pub fn new(size: f32) -> LayoutSettings<'a> {
+        LayoutSettings {
+            size: size,
+            x: 0.0,
+            y: 0.0,
+            tab_width: 4,
+            fallbacks: &DEFAULT_FALLBACKS,
+        }
+    }
+}
+
+/// Lay out a string with a bitmap font atlas, producing a quad for every visible glyph.
+///
+/// The pen starts on the baseline at the position given in the settings. Newlines move the
+/// pen back to the start of the next line, tabs move the pen to the next tab stop, and other
+/// whitespace advances the pen without producing a quad. The kerning table of the atlas
+/// adjusts the spacing between neighboring glyphs.
+/// Visible characters missing from the atlas are drawn with the first fallback character of the
+/// settings the atlas has, and failing that with the `.notdef` glyph of the atlas. When
+/// there is no such glyph, they produce no quad, and advance the pen by the width of a space
+/// so the gap stays visible.
+/// An atlas whose font size is not a positive number produces no glyphs at all.
+pub fn layout(atlas: &BitmapFontAtlas, text: &str, settings: &LayoutSettings) -> Vec<PositionedGlyph> {
+    if !(atlas.font_size.is_finite() && atlas.font_size > 0.0) {
+        return vec![];
+    }
+
+    // The glyph metrics are normalized by the slot glyph size at the font size the atlas
+    // was rasterized at.
+    let scale = settings.size * atlas.slot_glyph_size as f32 / atlas.font_size;
+    let line_height = atlas.line_height() * scale;
+    let space_advance = atlas.glyph(' ')
+        .map(|glyph| glyph.advance * scale)
+        .unwrap_or(0.5 * scale);
+    let tab_advance = space_advance * settings.tab_width as f32;
+
+    let mut glyphs = vec![];
+    let mut pen_x = settings.x;
+    let mut pen_y = settings.y;
+    let mut previous = None;
+    for ch in text.chars() {
+        match ch {
+            '\n' => {
+                pen_x = settings.x;
+                pen_y += line_height;
+                previous = None;
+                continue;
+            }
+            '\r' => continue,
+            '\t' => {
+                if tab_advance > 0.0 {
+                    let tab_stops = f32::floor((pen_x - settings.x) / tab_advance) + 1.0;
+                    pen_x = settings.x + tab_stops * tab_advance;
+                }
+                previous = None;
+                continue;
+            }
+            _ => {}
+        }
+
+        // Missing whitespace only leaves a gap, instead of drawing a fallback glyph.
+        let glyph = if ch.is_whitespace() {
+            atlas.glyph(ch)
+        } else {
+            atlas.glyph_or_fallback(ch, settings.fallbacks)
+        };
+        let glyph = match glyph {
+            Some(glyph) => glyph,
+            None => {
+                pen_x += space_advance;
+                previous = None;
+                continue;
+            }
+        };
+        if let Some(previous) = previous {
+            pen_x += atlas.kern(previous, glyph.code_point) * scale;
+        }
+        previous = Some(glyph.code_point);
+
+        let region = atlas.glyph_region(glyph);
+        if !ch.is_whitespace() && region.width > 0 && region.height > 0 {
+            let width = region.width as f32 * scale / atlas.slot_glyph_size as f32;
+            let height = region.height as f32 * scale / atlas.slot_glyph_size as f32;
+            let bottom = pen_y - glyph.y_offset * scale;
+            glyphs.push(PositionedGlyph {
+                code_point: glyph.code_point,
+                page: glyph.page,
+                screen_rect: Rect::new(pen_x + glyph.x_offset * scale, bottom - height, width, height),
+                uv_rect: atlas.glyph_region_uv_rect(glyph, atlas.origin),
+            });
+        }
+
+        pen_x += glyph.advance * scale;
+    }
+
+    glyphs
+}
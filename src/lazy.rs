@@ -0,0 +1,134 @@
+use crate::{
+    BitmapFontAtlas,
+    BitmapFontAtlasImage,
+    BitmapFontAtlasMetadata,
+    Error,
+    ErrorKind,
+    ReadLimits,
+    ReadOptions,
+};
+use crate::validate::Diagnostic;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+
+/// A `LazyBitmapFontAtlas` is a bitmap font atlas whose metadata is read up front, but
+/// whose pages are only decoded the first time their pixels are requested.
+pub struct LazyBitmapFontAtlas<R> {
+    /// The archive the pages are decoded from.
+    zip: zip::ZipArchive<R>,
+    /// The metadata of the atlas.
+    metadata: BitmapFontAtlasMetadata,
+    /// The pages decoded so far.
+    pages: Vec<Option<BitmapFontAtlasImage>>,
+    /// The resource limits enforced while decoding pages.
+    limits: ReadLimits,
+    /// The number of bytes the pages decoded from now on may still occupy.
+    budget: usize,
+    /// Whether to reject pages that do not match the dimensions of the atlas.
+    strict: bool,
+}
+
+impl<R: io::Read + io::Seek> LazyBitmapFontAtlas<R> {
+    /// Return the metadata of the atlas.
+    #[inline]
+    pub fn metadata(&self) -> &BitmapFontAtlasMetadata {
+        &self.metadata
+    }
+
+    /// Return the number of pages of the atlas.
+    #[inline]
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Determine whether a page has been decoded yet.
+    pub fn is_page_decoded(&self, page: usize) -> bool {
+        self.pages.get(page).is_some_and(|page| page.is_some())
+    }
+
+    /// Return an atlas page, decoding it first if this is the first request for it.
+    pub fn page(&mut self, page: usize) -> Result<&BitmapFontAtlasImage, Error> {
+        if page >= self.pages.len() {
+            return Err(Error::new(
+                ErrorKind::FontAtlasImageNotFound,
+                format!("the font atlas has no page {}", page).into()
+            ));
+        }
+        if self.pages[page].is_none() {
+            let image = crate::read_page(
+                &mut self.zip, page, self.pages.len(), self.metadata.origin, &self.limits, &mut self.budget
+            )?;
+            if self.strict && (image.width() != self.metadata.width || image.height() != self.metadata.height) {
+                crate::reject_diagnostics(&[Diagnostic::ImageDimensionMismatch {
+                    page: page,
+                    width: image.width(),
+                    height: image.height(),
+                    len_bytes: image.len_bytes(),
+                }])?;
+            }
+            self.pages[page] = Some(image);
+        }
+
+        Ok(self.pages[page].as_ref().unwrap())
+    }
+
+    /// Decode every remaining page and convert this into a fully loaded bitmap font atlas.
+    pub fn into_atlas(mut self) -> Result<BitmapFontAtlas, Error> {
+        for page in 0..self.pages.len() {
+            self.page(page)?;
+        }
+        let pages = self.pages.into_iter().flatten().collect();
+
+        Ok(BitmapFontAtlas::new(self.metadata, pages))
+    }
+}
+
+/// Read in a bitmap font atlas from an external source, deferring the decoding of its
+/// pages until they are requested.
+pub fn from_reader<R: io::Read + io::Seek>(reader: R) -> Result<LazyBitmapFontAtlas<R>, Error> {
+    from_reader_with_options(reader, &ReadOptions::new())
+}
+
+/// Read in a bitmap font atlas from an external source with the given options, deferring
+/// the decoding of its pages until they are requested. Strict reading checks the metadata
+/// up front, and each page against the atlas dimensions as it is decoded.
+pub fn from_reader_with_options<R: io::Read + io::Seek>(
+    reader: R, options: &ReadOptions) -> Result<LazyBitmapFontAtlas<R>, Error> {
+
+    let mut zip = crate::open_archive(reader)?;
+    let metadata = crate::read_metadata(&mut zip, &options.limits)?;
+    if metadata.page_count == 0 {
+        return Err(Error::new(ErrorKind::FontAtlasImageNotFound, "the font atlas has no pages".into()));
+    }
+    if options.strict {
+        crate::reject_diagnostics(&metadata.validate())?;
+    }
+    let pages = vec![None; metadata.page_count];
+
+    Ok(LazyBitmapFontAtlas {
+        zip: zip,
+        metadata: metadata,
+        pages: pages,
+        limits: options.limits,
+        budget: options.limits.max_decompressed_size,
+        strict: options.strict,
+    })
+}
+
+/// Load a bitmap font atlas from a file, deferring the decoding of its pages until they
+/// are requested.
+pub fn load<P: AsRef<Path>>(path: P) -> Result<LazyBitmapFontAtlas<File>, Error> {
+    load_with_options(path, &ReadOptions::new())
+}
+
+/// Load a bitmap font atlas from a file with the given options, deferring the decoding
+/// of its pages until they are requested.
+pub fn load_with_options<P: AsRef<Path>>(path: P, options: &ReadOptions) -> Result<LazyBitmapFontAtlas<File>, Error> {
+    let reader = File::open(&path).map_err(|e| {
+        Error::new(ErrorKind::FileNotFound, Box::new(e))
+    })?;
+
+    from_reader_with_options(reader, options)
+}
@@ -1,13 +1,50 @@
+pub mod bmfont;
+pub mod generator;
+pub mod glyph_image;
+pub mod layout;
+pub mod lazy;
+pub mod mesh;
+pub mod packing;
+pub mod render;
+pub mod validate;
+
 use serde_derive::{Serialize, Deserialize};
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
 use std::error;
 use std::fmt;
 use std::fs::File;
 use std::io;
 use std::path::Path;
-use image::png;
-use image::{ColorType, ImageDecoder};
+use image::codecs::png;
+use image::{ColorType, ImageDecoder, ImageEncoder};
+
+
+/// A `GlyphRect` is a rectangle of pixels in the atlas image. The coordinates are measured
+/// from the top left corner of the atlas image as it is stored in the PNG file, regardless of
+/// the origin of the atlas.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct GlyphRect {
+    /// The horizontal position of the left edge of the rectangle, in pixels.
+    pub x: usize,
+    /// The vertical position of the top edge of the rectangle, in pixels.
+    pub y: usize,
+    /// The width of the rectangle, in pixels.
+    pub width: usize,
+    /// The height of the rectangle, in pixels.
+    pub height: usize,
+}
 
+impl GlyphRect {
+    pub fn new(x: usize, y: usize, width: usize, height: usize) -> GlyphRect {
+        GlyphRect {
+            x: x,
+            y: y,
+            width: width,
+            height: height,
+        }
+    }
+}
 
 /// A `GlyphMetadata` struct stores the parameters necessary to represent
 /// the glyph in a bitmap font atlas.
@@ -28,9 +65,25 @@ pub struct GlyphMetadata {
     /// The maximum depth of the glyph that falls below the baseline for the font.
     pub y_min: f32,
     pub y_offset: f32,
+    /// The horizontal distance from the pen position to the left edge of the glyph,
+    /// normalized by the slot glyph size.
+    #[serde(default)]
+    pub x_offset: f32,
+    /// The horizontal distance to move the pen after drawing the glyph, normalized by the
+    /// slot glyph size.
+    pub advance: f32,
+    /// The atlas page the glyph is stored on.
+    #[serde(default)]
+    pub page: usize,
+    /// The pixel rectangle the glyph occupies in a packed atlas. Glyphs in a grid atlas
+    /// are located by their row and column instead.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rect: Option<GlyphRect>,
 }
 
 impl GlyphMetadata {
+    /// Construct the metadata for a glyph. The advance of the glyph starts out as its width,
+    /// and the glyph starts out drawn at the pen position.
     pub fn new(
         code_point: usize, row: usize, column: usize,
         width: f32, height: f32,
@@ -45,6 +98,10 @@ impl GlyphMetadata {
             x_min: x_min,
             y_min: y_min,
             y_offset: y_offset,
+            x_offset: 0.0,
+            advance: width,
+            page: 0,
+            rect: None,
         }
     }
 }
@@ -62,6 +119,27 @@ pub enum Origin {
     BottomLeft,
 }
 
+/// The `AtlasLayout` parameter determines how the glyphs are arranged in the atlas image.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum AtlasLayout {
+    /// Every glyph occupies one square slot in a grid of `rows` by `columns` slots.
+    #[default]
+    Grid,
+    /// Every glyph occupies its own tightly packed pixel rectangle.
+    Packed,
+}
+
+/// The `DistanceField` parameters describe how to decode an atlas image that stores signed
+/// distances to the glyph outlines instead of glyph coverage.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DistanceField {
+    /// The distance from a glyph outline, in pixels, at which the stored distance values
+    /// saturate. A stored value of one half lies on the outline, a stored value of zero lies
+    /// `range` pixels outside the glyph, and a stored value of one lies `range` pixels inside
+    /// the glyph.
+    pub range: f32,
+}
+
 /// The `BitmapFontAtlasMetadata` struct holds all the information about the image
 /// and every glyph in the font atlas, including where each glyph is located in the
 /// atlas image for rendering text.
@@ -69,6 +147,10 @@ pub enum Origin {
 pub struct BitmapFontAtlasMetadata {
     /// The origin of the image. This determines the coordinate system and orientation of the image.
     pub origin: Origin,
+    /// The arrangement of the glyphs in the atlas image. Older atlases are always laid
+    /// out in a grid.
+    #[serde(default)]
+    pub layout: AtlasLayout,
     /// The width of the image, in pixels.
     pub width: usize,
     /// The height of the image, in pixels.
@@ -83,8 +165,261 @@ pub struct BitmapFontAtlasMetadata {
     pub slot_glyph_size: usize,
     /// The size of a glyph inside a glyph slot, in pixels.
     pub glyph_size: usize,
+    /// The pixel size the font was rasterized at.
+    pub font_size: f32,
+    /// The distance from the baseline to the top of the tallest glyphs, normalized by
+    /// the slot glyph size.
+    pub ascent: f32,
+    /// The distance from the baseline to the bottom of the deepest glyphs, normalized by
+    /// the slot glyph size. This is negative for glyphs that fall below the baseline.
+    pub descent: f32,
+    /// The extra space between the descent of one line and the ascent of the next line,
+    /// normalized by the slot glyph size.
+    pub line_gap: f32,
+    /// The number of atlas pages. Every page has the same dimensions. Older atlases
+    /// always have exactly one page.
+    #[serde(default = "default_page_count")]
+    pub page_count: usize,
     /// The table containing the metadata for each glyph.
+    #[serde(with = "glyph_table")]
     pub glyph_metadata: HashMap<usize, GlyphMetadata>,
+    /// The kerning table mapping pairs of code points to the horizontal adjustment
+    /// between them, normalized by the slot glyph size. Pairs missing from the table
+    /// need no adjustment.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty", with = "kerning_table")]
+    pub kerning: HashMap<(usize, usize), f32>,
+    /// The distance field parameters, if the atlas image stores signed distances
+    /// instead of glyph coverage.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub distance_field: Option<DistanceField>,
+    /// The code point of the glyph drawn for characters the atlas has no glyph for, in
+    /// the role of the `.notdef` glyph of a font.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notdef: Option<usize>,
+}
+
+/// The glyph table is stored in the metadata file in code point order, so that writing
+/// the same atlas twice produces the same file.
+mod glyph_table {
+    use super::GlyphMetadata;
+    use serde::de::{self, IgnoredAny, MapAccess, Visitor};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::{BTreeMap, HashMap};
+    use std::fmt;
+
+    pub fn serialize<S: Serializer>(table: &HashMap<usize, GlyphMetadata>, serializer: S) -> Result<S::Ok, S::Error> {
+        let sorted: BTreeMap<&usize, &GlyphMetadata> = table.iter().collect();
+
+        sorted.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<HashMap<usize, GlyphMetadata>, D::Error> {
+        HashMap::deserialize(deserializer)
+    }
+
+    /// Count the entries of the glyph table in serialized metadata without allocating
+    /// anything for them, so that the glyph limit can be enforced before the metadata gets
+    /// parsed.
+    pub fn count(metadata: &[u8]) -> Result<usize, serde_json::Error> {
+        let mut deserializer = serde_json::Deserializer::from_slice(metadata);
+        let count = deserializer.deserialize_map(MetadataVisitor)?;
+        deserializer.end()?;
+
+        Ok(count)
+    }
+
+    /// Visits the fields of the metadata, counting the entries of every glyph table.
+    struct MetadataVisitor;
+
+    impl<'de> Visitor<'de> for MetadataVisitor {
+        type Value = usize;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("the font atlas metadata")
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<usize, A::Error> {
+            let mut count: usize = 0;
+            while let Some(field) = map.next_key::<Field>()? {
+                match field {
+                    Field::GlyphMetadata => count = count.saturating_add(map.next_value::<GlyphCount>()?.0),
+                    Field::Other => { map.next_value::<IgnoredAny>()?; }
+                }
+            }
+
+            Ok(count)
+        }
+    }
+
+    /// A field of the metadata, telling the glyph table apart from the other fields.
+    enum Field {
+        GlyphMetadata,
+        Other,
+    }
+
+    impl<'de> Deserialize<'de> for Field {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Field, D::Error> {
+            struct FieldVisitor;
+
+            impl<'de> Visitor<'de> for FieldVisitor {
+                type Value = Field;
+
+                fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                    formatter.write_str("a field name")
+                }
+
+                fn visit_str<E: de::Error>(self, value: &str) -> Result<Field, E> {
+                    if value == "glyph_metadata" {
+                        Ok(Field::GlyphMetadata)
+                    } else {
+                        Ok(Field::Other)
+                    }
+                }
+            }
+
+            deserializer.deserialize_identifier(FieldVisitor)
+        }
+    }
+
+    /// The number of entries in a glyph table.
+    struct GlyphCount(usize);
+
+    impl<'de> Deserialize<'de> for GlyphCount {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<GlyphCount, D::Error> {
+            struct GlyphCountVisitor;
+
+            impl<'de> Visitor<'de> for GlyphCountVisitor {
+                type Value = GlyphCount;
+
+                fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                    formatter.write_str("a glyph table")
+                }
+
+                fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<GlyphCount, A::Error> {
+                    let mut count: usize = 0;
+                    while map.next_entry::<IgnoredAny, IgnoredAny>()?.is_some() {
+                        count = count.saturating_add(1);
+                    }
+
+                    Ok(GlyphCount(count))
+                }
+            }
+
+            deserializer.deserialize_map(GlyphCountVisitor)
+        }
+    }
+}
+
+/// A `KerningPair` is one entry of the kerning table as it is stored in the metadata file.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct KerningPair {
+    first: usize,
+    second: usize,
+    amount: f32,
+}
+
+/// The kerning table is stored in the metadata file as a list of pairs, since JSON objects
+/// cannot have pairs of code points as keys.
+mod kerning_table {
+    use super::KerningPair;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::HashMap;
+
+    pub fn serialize<S: Serializer>(table: &HashMap<(usize, usize), f32>, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut pairs: Vec<KerningPair> = table.iter()
+            .map(|(&(first, second), &amount)| KerningPair { first: first, second: second, amount: amount })
+            .collect();
+        pairs.sort_by_key(|pair| (pair.first, pair.second));
+
+        pairs.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<HashMap<(usize, usize), f32>, D::Error> {
+        let pairs: Vec<KerningPair> = Vec::deserialize(deserializer)?;
+
+        Ok(pairs.into_iter().map(|pair| ((pair.first, pair.second), pair.amount)).collect())
+    }
+}
+
+fn default_page_count() -> usize {
+    1
+}
+
+/// The `PixelFormat` parameter describes the channels of each pixel of an atlas image.
+/// The channels of the 16-bit formats are stored in native byte order.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum PixelFormat {
+    /// One 8-bit channel holding the glyph coverage.
+    R8,
+    /// Two 8-bit channels.
+    Rg8,
+    /// Three 8-bit channels.
+    Rgb8,
+    /// Four 8-bit channels.
+    Rgba8,
+    /// One 16-bit channel holding the glyph coverage.
+    R16,
+    /// Two 16-bit channels.
+    Rg16,
+    /// Three 16-bit channels.
+    Rgb16,
+    /// Four 16-bit channels.
+    Rgba16,
+}
+
+impl PixelFormat {
+    /// Return the number of channels in each pixel.
+    pub fn channel_count(self) -> usize {
+        match self {
+            PixelFormat::R8 | PixelFormat::R16 => 1,
+            PixelFormat::Rg8 | PixelFormat::Rg16 => 2,
+            PixelFormat::Rgb8 | PixelFormat::Rgb16 => 3,
+            PixelFormat::Rgba8 | PixelFormat::Rgba16 => 4,
+        }
+    }
+
+    /// Return the size of each channel, in bytes.
+    pub fn bytes_per_channel(self) -> usize {
+        match self {
+            PixelFormat::R8 | PixelFormat::Rg8 | PixelFormat::Rgb8 | PixelFormat::Rgba8 => 1,
+            PixelFormat::R16 | PixelFormat::Rg16 | PixelFormat::Rgb16 | PixelFormat::Rgba16 => 2,
+        }
+    }
+
+    /// Return the size of each pixel, in bytes.
+    pub fn bytes_per_pixel(self) -> usize {
+        self.channel_count() * self.bytes_per_channel()
+    }
+
+    /// Return the pixel format matching a PNG color type, if there is one. Grayscale
+    /// images map onto the red and green channels.
+    pub(crate) fn from_color_type(color_type: ColorType) -> Option<PixelFormat> {
+        match color_type {
+            ColorType::L8 => Some(PixelFormat::R8),
+            ColorType::La8 => Some(PixelFormat::Rg8),
+            ColorType::Rgb8 => Some(PixelFormat::Rgb8),
+            ColorType::Rgba8 => Some(PixelFormat::Rgba8),
+            ColorType::L16 => Some(PixelFormat::R16),
+            ColorType::La16 => Some(PixelFormat::Rg16),
+            ColorType::Rgb16 => Some(PixelFormat::Rgb16),
+            ColorType::Rgba16 => Some(PixelFormat::Rgba16),
+            _ => None,
+        }
+    }
+
+    /// Return the PNG color type that stores pixels of this format.
+    pub(crate) fn color_type(self) -> ColorType {
+        match self {
+            PixelFormat::R8 => ColorType::L8,
+            PixelFormat::Rg8 => ColorType::La8,
+            PixelFormat::Rgb8 => ColorType::Rgb8,
+            PixelFormat::Rgba8 => ColorType::Rgba8,
+            PixelFormat::R16 => ColorType::L16,
+            PixelFormat::Rg16 => ColorType::La16,
+            PixelFormat::Rgb16 => ColorType::Rgb16,
+            PixelFormat::Rgba16 => ColorType::Rgba16,
+        }
+    }
 }
 
 /// A `BitmapFontAtlasImage` represents the underlying bitmapped image containing the
@@ -97,16 +432,19 @@ pub struct BitmapFontAtlasImage {
     width: usize,
     /// The height of the image, in pixels.
     height: usize,
+    /// The layout of the channels of each pixel.
+    format: PixelFormat,
     /// The underlying raw image data.
     data: Vec<u8>,
 }
 
 impl BitmapFontAtlasImage {
-    pub fn new(data: Vec<u8>, width: usize, height: usize, origin: Origin) -> BitmapFontAtlasImage {
+    pub fn new(data: Vec<u8>, width: usize, height: usize, format: PixelFormat, origin: Origin) -> BitmapFontAtlasImage {
         BitmapFontAtlasImage {
             origin: origin,
             width: width,
             height: height,
+            format: format,
             data: data,
         }
     }
@@ -123,6 +461,50 @@ impl BitmapFontAtlasImage {
         self.height
     }
 
+    /// Return the pixel format of the image.
+    #[inline]
+    pub fn format(&self) -> PixelFormat {
+        self.format
+    }
+
+    /// Return the origin of the image.
+    #[inline]
+    pub fn origin(&self) -> Origin {
+        self.origin
+    }
+
+    /// Convert the image to a different origin. The image stores its rows from the origin
+    /// outwards, so changing between a top left and a bottom left origin reverses the order
+    /// of the rows.
+    pub fn convert_origin(&mut self, origin: Origin) {
+        if self.origin != origin {
+            let width_in_bytes = self.width_in_bytes();
+            flip_vertically(&mut self.data, width_in_bytes, self.height);
+            self.origin = origin;
+        }
+    }
+
+    /// Return the length of one row of the image, in bytes.
+    #[inline]
+    pub fn width_in_bytes(&self) -> usize {
+        self.format.bytes_per_pixel() * self.width
+    }
+
+    /// Return the length of a buffer holding the image, in bytes, or `None` when the length
+    /// does not fit into a `usize`.
+    pub(crate) fn checked_len_bytes(&self) -> Option<usize> {
+        self.format.bytes_per_pixel().checked_mul(self.width)?.checked_mul(self.height)
+    }
+
+    /// Return the bytes of the pixel at column `x` and row `y` of the image, counting rows
+    /// in the order they are stored in memory.
+    pub fn pixel(&self, x: usize, y: usize) -> &[u8] {
+        let bytes_per_pixel = self.format.bytes_per_pixel();
+        let offset = y * self.width_in_bytes() + x * bytes_per_pixel;
+
+        &self.data[offset..(offset + bytes_per_pixel)]
+    }
+
     /// Return a pointer to the underlying image data.
     #[inline]
     pub fn as_ptr(&self) -> *const u8 {
@@ -142,11 +524,18 @@ impl AsRef<[u8]> for BitmapFontAtlasImage {
     }
 }
 
+/// The fallback characters tried for characters missing from an atlas, before the
+/// `.notdef` glyph of the atlas: the replacement character, then the question mark.
+pub const DEFAULT_FALLBACKS: [char; 2] = ['\u{FFFD}', '?'];
+
 /// A `BitmapFontAtlas` is a bitmapped font sheet. It contains the glyph parameters necessary to
-/// index into the bitmap image as well as the bitmap image itself.
+/// index into the bitmap images as well as the bitmap images themselves. Large character sets
+/// can span several atlas pages.
 pub struct BitmapFontAtlas {
     /// The origin of the image. This determines the coordinate system and orientation of the image.
     pub origin: Origin,
+    /// The arrangement of the glyphs in the atlas image.
+    pub layout: AtlasLayout,
     /// The width of the image, in pixels.
     pub width: usize,
     /// The height of the image, in pixels.
@@ -161,16 +550,35 @@ pub struct BitmapFontAtlas {
     pub slot_glyph_size: usize,
     /// The size of a glyph inside a glyph slot, in pixels.
     pub glyph_size: usize,
+    /// The pixel size the font was rasterized at.
+    pub font_size: f32,
+    /// The distance from the baseline to the top of the tallest glyphs, normalized by
+    /// the slot glyph size.
+    pub ascent: f32,
+    /// The distance from the baseline to the bottom of the deepest glyphs, normalized by
+    /// the slot glyph size.
+    pub descent: f32,
+    /// The extra space between lines, normalized by the slot glyph size.
+    pub line_gap: f32,
     /// The table containing the metadata for each glyph.
     pub glyph_metadata: HashMap<usize, GlyphMetadata>,
-    /// The array containing the font atlas image itself.
-    pub image: BitmapFontAtlasImage,
+    /// The kerning table mapping pairs of code points to the horizontal adjustment
+    /// between them, normalized by the slot glyph size.
+    pub kerning: HashMap<(usize, usize), f32>,
+    /// The distance field parameters, if the atlas image stores signed distances
+    /// instead of glyph coverage.
+    pub distance_field: Option<DistanceField>,
+    /// The code point of the glyph drawn for characters the atlas has no glyph for.
+    pub notdef: Option<usize>,
+    /// The font atlas pages. Each glyph records the page it is stored on.
+    pub pages: Vec<BitmapFontAtlasImage>,
 }
 
 impl BitmapFontAtlas {
-    pub fn new(metadata: BitmapFontAtlasMetadata, image: BitmapFontAtlasImage) -> BitmapFontAtlas {
+    pub fn new(metadata: BitmapFontAtlasMetadata, pages: Vec<BitmapFontAtlasImage>) -> BitmapFontAtlas {
         BitmapFontAtlas {
             origin: metadata.origin,
+            layout: metadata.layout,
             width: metadata.width,
             height: metadata.height,
             columns: metadata.columns,
@@ -178,8 +586,15 @@ impl BitmapFontAtlas {
             padding: metadata.padding,
             slot_glyph_size: metadata.slot_glyph_size,
             glyph_size: metadata.glyph_size,
+            font_size: metadata.font_size,
+            ascent: metadata.ascent,
+            descent: metadata.descent,
+            line_gap: metadata.line_gap,
             glyph_metadata: metadata.glyph_metadata,
-            image: image,
+            kerning: metadata.kerning,
+            distance_field: metadata.distance_field,
+            notdef: metadata.notdef,
+            pages: pages,
         }
     }
 
@@ -187,6 +602,7 @@ impl BitmapFontAtlas {
     pub fn metadata(&self) -> BitmapFontAtlasMetadata {
         BitmapFontAtlasMetadata {
             origin: self.origin,
+            layout: self.layout,
             width: self.width,
             height: self.height,
             columns: self.columns,
@@ -194,50 +610,407 @@ impl BitmapFontAtlas {
             padding: self.padding,
             slot_glyph_size: self.slot_glyph_size,
             glyph_size: self.glyph_size,
+            font_size: self.font_size,
+            ascent: self.ascent,
+            descent: self.descent,
+            line_gap: self.line_gap,
+            page_count: self.pages.len(),
             glyph_metadata: self.glyph_metadata.clone(),
+            kerning: self.kerning.clone(),
+            distance_field: self.distance_field,
+            notdef: self.notdef,
+        }
+    }
+
+    /// Return the glyph for a character, if the atlas has one.
+    pub fn glyph(&self, ch: char) -> Option<&GlyphMetadata> {
+        self.glyph_metadata.get(&(ch as usize))
+    }
+
+    /// Return the glyph for a character. When the atlas has no glyph for it, return the
+    /// glyph of the first fallback character the atlas has, and failing that the
+    /// `.notdef` glyph of the atlas.
+    pub fn glyph_or_fallback(&self, ch: char, fallbacks: &[char]) -> Option<&GlyphMetadata> {
+        self.glyph(ch)
+            .or_else(|| fallbacks.iter().find_map(|&fallback| self.glyph(fallback)))
+            .or_else(|| self.notdef.and_then(|notdef| self.glyph_metadata.get(&notdef)))
+    }
+
+    /// Return the distance between the baselines of consecutive lines of text, normalized
+    /// by the slot glyph size.
+    pub fn line_height(&self) -> f32 {
+        self.ascent - self.descent + self.line_gap
+    }
+
+    /// Return the horizontal adjustment between a pair of glyphs, normalized by the slot
+    /// glyph size. This is zero when the kerning table has no entry for the pair.
+    pub fn kern(&self, first: usize, second: usize) -> f32 {
+        self.kerning.get(&(first, second)).cloned().unwrap_or(0.0)
+    }
+
+    /// Convert the atlas to a different origin, reversing the rows of every page and
+    /// rewriting the glyph offsets measured from the origin. The rows, columns and pixel
+    /// rectangles of the glyphs are measured from the top of the PNG file, so they stay
+    /// the same.
+    pub fn convert_origin(&mut self, origin: Origin) {
+        if self.origin == origin {
+            return;
+        }
+        for page in self.pages.iter_mut() {
+            page.convert_origin(origin);
+        }
+        for glyph in self.glyph_metadata.values_mut() {
+            // The offset locates the edge of the region of the glyph nearest the origin, so
+            // it moves to the opposite edge of the region.
+            let region_height = glyph.rect.map_or(self.slot_glyph_size, |rect| rect.height);
+            glyph.y_min = 1.0 - glyph.y_min - region_height as f32 / self.height as f32;
         }
+        self.origin = origin;
+    }
+
+    /// Return the pixel rectangle a glyph occupies on its atlas page, measured from the
+    /// top left corner of the page as it is stored in the PNG file. Glyphs in a grid atlas
+    /// occupy the top left corner of their glyph slot.
+    pub(crate) fn glyph_region(&self, glyph: &GlyphMetadata) -> GlyphRect {
+        match glyph.rect {
+            Some(rect) => rect,
+            None => {
+                let slot = self.slot_glyph_size as f32;
+                let width = f32::round(glyph.width * slot) as usize;
+                let height = f32::round(glyph.height * slot) as usize;
+                GlyphRect::new(
+                    glyph.column * self.slot_glyph_size,
+                    glyph.row * self.slot_glyph_size,
+                    usize::min(width, self.slot_glyph_size),
+                    usize::min(height, self.slot_glyph_size)
+                )
+            }
+        }
+    }
+
+    /// Return the texture coordinates of the pixel rectangle of a glyph, in the convention
+    /// of the given origin.
+    pub(crate) fn glyph_region_uv_rect(&self, glyph: &GlyphMetadata, origin: Origin) -> layout::Rect {
+        let region = self.glyph_region(glyph);
+        let (width, height) = (self.width as f32, self.height as f32);
+        let u = region.x as f32 / width;
+        let v = match origin {
+            Origin::TopLeft => region.y as f32 / height,
+            Origin::BottomLeft => 1.0 - (region.y + region.height) as f32 / height,
+        };
+
+        layout::Rect::new(u, v, region.width as f32 / width, region.height as f32 / height)
+    }
+
+    /// Return the pixel rectangle the glyph for a code point occupies on its atlas page,
+    /// measured from the top left corner of the page as it is stored in the PNG file. The
+    /// rectangle spans the pixels from `x` up to but excluding `x + width`, and likewise
+    /// vertically. Glyphs in a grid atlas occupy the top left corner of their glyph slot.
+    pub fn glyph_rect(&self, code_point: usize) -> Option<GlyphRect> {
+        self.glyph_metadata.get(&code_point).map(|glyph| self.glyph_region(glyph))
+    }
+
+    /// Return the texture coordinates of the glyph for a code point in its atlas page,
+    /// normalized by the page dimensions. The rectangle runs along the pixel edges of the
+    /// glyph, not through the pixel centers.
+    ///
+    /// The coordinates follow the convention of the given origin rather than the origin of
+    /// the atlas. With a top left origin, as in Vulkan and Direct3D, `v` grows downwards
+    /// from the top of the page and the minimum corner is the top left corner of the glyph.
+    /// With a bottom left origin, as in OpenGL, `v` grows upwards from the bottom of the
+    /// page and the minimum corner is the bottom left corner of the glyph. The convention
+    /// matching an uploaded page is the origin of the atlas, since the pages store their
+    /// rows from the origin outwards.
+    pub fn glyph_uv_rect(&self, code_point: usize, origin: Origin) -> Option<layout::Rect> {
+        self.glyph_metadata.get(&code_point).map(|glyph| self.glyph_region_uv_rect(glyph, origin))
     }
 }
 
 impl AsRef<[u8]> for BitmapFontAtlas {
+    /// Return the image data of the first atlas page, or an empty slice for an atlas
+    /// without pages. Use `pages[i].as_ref()` for the image data of the other pages.
     #[inline]
     fn as_ref(&self) -> &[u8] {
-        self.image.as_ref()
+        self.pages.first().map_or(&[], |page| page.as_ref())
+    }
+}
+
+/// A `GlyphBitmap` is the image of a single glyph along with the pixel metrics needed to
+/// place it on a line of text.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GlyphBitmap {
+    /// The width of the image, in pixels.
+    pub width: usize,
+    /// The height of the image, in pixels.
+    pub height: usize,
+    /// The pixels of the image in the pixel format of the atlas, stored row by row from
+    /// the top of the glyph down.
+    pub data: Vec<u8>,
+    /// The horizontal distance from the pen position to the left edge of the image, in pixels.
+    pub x_offset: f32,
+    /// The distance from the baseline up to the bottom edge of the image, in pixels.
+    /// Glyphs that descend below the baseline have a negative offset.
+    pub y_offset: f32,
+    /// The horizontal distance to move the pen after drawing the glyph, in pixels.
+    pub advance: f32,
+}
+
+impl GlyphBitmap {
+    /// Construct a glyph bitmap that sits on the baseline at the pen position. The advance
+    /// of the glyph starts out as its width.
+    pub fn new(data: Vec<u8>, width: usize, height: usize) -> GlyphBitmap {
+        GlyphBitmap {
+            width: width,
+            height: height,
+            data: data,
+            x_offset: 0.0,
+            y_offset: 0.0,
+            advance: width as f32,
+        }
     }
 }
 
-struct BitmapFontAtlasBuilder {
-    metadata: BitmapFontAtlasMetadata,
-    image: BitmapFontAtlasImage,
+/// A `BitmapFontAtlasBuilder` assembles a grid layout bitmap font atlas from individual
+/// glyph bitmaps.
+///
+/// The glyphs are placed into the slots of the grid in code point order, from the top left
+/// corner of the atlas image, and each glyph is drawn inset by half the padding from the
+/// corner of its slot. All pixel metrics given to the builder get normalized by the slot
+/// glyph size when the atlas is built.
+#[derive(Clone, Debug)]
+pub struct BitmapFontAtlasBuilder {
+    origin: Origin,
+    format: PixelFormat,
+    slot_glyph_size: usize,
+    padding: usize,
+    columns: Option<usize>,
+    font_size: Option<f32>,
+    line_metrics: Option<(f32, f32, f32)>,
+    glyphs: BTreeMap<usize, GlyphBitmap>,
+    kerning: HashMap<(usize, usize), f32>,
+    notdef: Option<usize>,
 }
 
 impl BitmapFontAtlasBuilder {
-    fn new(metadata: BitmapFontAtlasMetadata, image: BitmapFontAtlasImage) -> BitmapFontAtlasBuilder {
+    /// Construct a builder for an atlas with a bottom left origin storing four 8-bit
+    /// channels per pixel.
+    pub fn new(slot_glyph_size: usize, padding: usize) -> BitmapFontAtlasBuilder {
         BitmapFontAtlasBuilder {
-            metadata: metadata,
-            image: image,
-        }
-    }
-
-    /// Build the font atlas and consume the builder.
-    fn build(mut self) -> BitmapFontAtlas {
-        // If the origin is declared as the bottom left, we must flip the image since the
-        // PNG image format indexes the image starting from the top left corner
-        // going right and downwards.
-        if self.metadata.origin == Origin::BottomLeft {
-            let height = self.image.height;
-            let width_in_bytes = 4 * self.image.width;
-            let half_height = self.image.height / 2;
-            for row in 0..half_height {
-                for col in 0..width_in_bytes {
-                    let temp = self.image.data[row * width_in_bytes + col];
-                    self.image.data[row * width_in_bytes + col] = self.image.data[((height - row - 1) * width_in_bytes) + col];
-                    self.image.data[((height - row - 1) * width_in_bytes) + col] = temp;
-                }
+            origin: Origin::BottomLeft,
+            format: PixelFormat::Rgba8,
+            slot_glyph_size: slot_glyph_size,
+            padding: padding,
+            columns: None,
+            font_size: None,
+            line_metrics: None,
+            glyphs: BTreeMap::new(),
+            kerning: HashMap::new(),
+            notdef: None,
+        }
+    }
+
+    /// Set the origin of the atlas image.
+    pub fn origin(mut self, origin: Origin) -> BitmapFontAtlasBuilder {
+        self.origin = origin;
+        self
+    }
+
+    /// Set the pixel format of the atlas image and of the glyph bitmaps.
+    pub fn format(mut self, format: PixelFormat) -> BitmapFontAtlasBuilder {
+        self.format = format;
+        self
+    }
+
+    /// Set the number of glyph slots per row of the atlas. By default the grid is as
+    /// close to square as possible.
+    pub fn columns(mut self, columns: usize) -> BitmapFontAtlasBuilder {
+        self.columns = Some(columns);
+        self
+    }
+
+    /// Set the pixel size the glyphs were rasterized at. This defaults to the glyph size.
+    pub fn font_size(mut self, font_size: f32) -> BitmapFontAtlasBuilder {
+        self.font_size = Some(font_size);
+        self
+    }
+
+    /// Set the ascent, descent and line gap of the font, in pixels. By default the ascent
+    /// and descent are the extent of the visible glyphs around the baseline, and the line
+    /// gap is zero.
+    pub fn line_metrics(mut self, ascent: f32, descent: f32, line_gap: f32) -> BitmapFontAtlasBuilder {
+        self.line_metrics = Some((ascent, descent, line_gap));
+        self
+    }
+
+    /// Insert the bitmap of the glyph for a code point, replacing any bitmap inserted for
+    /// it before.
+    pub fn glyph(mut self, code_point: usize, bitmap: GlyphBitmap) -> BitmapFontAtlasBuilder {
+        self.glyphs.insert(code_point, bitmap);
+        self
+    }
+
+    /// Set the horizontal adjustment between a pair of glyphs, in pixels.
+    pub fn kerning(mut self, first: usize, second: usize, amount: f32) -> BitmapFontAtlasBuilder {
+        self.kerning.insert((first, second), amount);
+        self
+    }
+
+    /// Designate the glyph of a code point as the `.notdef` glyph, drawn for characters
+    /// the atlas has no glyph for.
+    pub fn notdef(mut self, code_point: usize) -> BitmapFontAtlasBuilder {
+        self.notdef = Some(code_point);
+        self
+    }
+
+    /// Check the parameters of the atlas and the glyph bitmaps.
+    fn validate(&self) -> Result<(), Error> {
+        let invalid = |message: String| Err(Error::new(ErrorKind::InvalidAtlasParameters, message.into()));
+        if self.slot_glyph_size == 0 {
+            return invalid(String::from("the slot glyph size must be positive"));
+        }
+        if self.padding >= self.slot_glyph_size {
+            return invalid(String::from("the padding must be smaller than the slot glyph size"));
+        }
+        if self.columns == Some(0) {
+            return invalid(String::from("the atlas must have at least one column"));
+        }
+        if self.glyphs.is_empty() {
+            return invalid(String::from("the atlas has no glyphs"));
+        }
+        if let Some(notdef) = self.notdef.filter(|notdef| !self.glyphs.contains_key(notdef)) {
+            return invalid(format!("the .notdef glyph {} has no bitmap", notdef));
+        }
+
+        let glyph_size = self.slot_glyph_size - self.padding;
+        for (code_point, bitmap) in self.glyphs.iter() {
+            if bitmap.width > glyph_size || bitmap.height > glyph_size {
+                return invalid(format!(
+                    "the bitmap of glyph {} is larger than the glyph size of {} pixels", code_point, glyph_size
+                ));
+            }
+            let length = bitmap.width.checked_mul(bitmap.height)
+                .and_then(|pixels| pixels.checked_mul(self.format.bytes_per_pixel()));
+            if length != Some(bitmap.data.len()) {
+                return invalid(format!(
+                    "the bitmap of glyph {} does not match its dimensions and pixel format", code_point
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Build the font atlas and consume the builder. This fails when the slot glyph size is
+    /// not larger than the padding, when there are no glyphs, when a glyph bitmap does not
+    /// fit inside its slot or does not match the pixel format, or when the columns make the
+    /// atlas page too large to store.
+    pub fn build(self) -> Result<BitmapFontAtlas, Error> {
+        self.validate()?;
+
+        let slot_glyph_size = self.slot_glyph_size;
+        let padding = self.padding;
+        let inset = padding / 2;
+        let glyph_count = self.glyphs.len();
+        let columns = self.columns.unwrap_or_else(|| f32::ceil(f32::sqrt(glyph_count as f32)) as usize);
+        let rows = glyph_count.div_ceil(columns);
+        let bytes_per_pixel = self.format.bytes_per_pixel();
+        // The page dimensions have to fit into the 32 bits of a PNG image header.
+        let page_size = columns.checked_mul(slot_glyph_size)
+            .zip(rows.checked_mul(slot_glyph_size))
+            .filter(|&(width, height)| width <= u32::MAX as usize && height <= u32::MAX as usize)
+            .and_then(|(width, height)| Some((width, height, width.checked_mul(height)?.checked_mul(bytes_per_pixel)?)));
+        let (width, height, length) = page_size.ok_or_else(|| {
+            Error::new(ErrorKind::InvalidAtlasParameters, "the atlas page is too large".into())
+        })?;
+
+        let slot = slot_glyph_size as f32;
+        let mut data = vec![0; length];
+        let mut glyph_metadata = HashMap::new();
+        let mut ascent: Option<f32> = None;
+        let mut descent: Option<f32> = None;
+        for (i, (&code_point, bitmap)) in self.glyphs.iter().enumerate() {
+            let (row, column) = (i / columns, i % columns);
+            let (x, y) = (column * slot_glyph_size + inset, row * slot_glyph_size + inset);
+            let bitmap_width_in_bytes = bitmap.width * bytes_per_pixel;
+            for bitmap_row in 0..bitmap.height {
+                let offset = ((y + bitmap_row) * width + x) * bytes_per_pixel;
+                let bitmap_offset = bitmap_row * bitmap_width_in_bytes;
+                data[offset..(offset + bitmap_width_in_bytes)]
+                    .copy_from_slice(&bitmap.data[bitmap_offset..(bitmap_offset + bitmap_width_in_bytes)]);
+            }
+
+            let is_empty = bitmap.width == 0 || bitmap.height == 0;
+            let (glyph_width, glyph_height) = if is_empty {
+                (f32::min(bitmap.advance / slot, 1.0), 0.0)
+            } else {
+                ((bitmap.width + padding) as f32 / slot, (bitmap.height + padding) as f32 / slot)
+            };
+            let y_offset = (bitmap.y_offset - (padding - inset) as f32) / slot;
+            let x_min = (column * slot_glyph_size) as f32 / width as f32;
+            let y_min = match self.origin {
+                Origin::TopLeft => (row * slot_glyph_size) as f32 / height as f32,
+                Origin::BottomLeft => 1.0 - ((row + 1) * slot_glyph_size) as f32 / height as f32,
+            };
+            let mut metadata = GlyphMetadata::new(
+                code_point, row, column, glyph_width, glyph_height, x_min, y_min, y_offset
+            );
+            metadata.x_offset = (bitmap.x_offset - inset as f32) / slot;
+            metadata.advance = bitmap.advance / slot;
+            glyph_metadata.insert(code_point, metadata);
+
+            let is_whitespace = std::char::from_u32(code_point as u32).is_some_and(|ch| ch.is_whitespace());
+            if !is_empty && !is_whitespace {
+                let top = bitmap.y_offset + bitmap.height as f32;
+                ascent = Some(ascent.map_or(top, |ascent| f32::max(ascent, top)));
+                descent = Some(descent.map_or(bitmap.y_offset, |descent| f32::min(descent, bitmap.y_offset)));
             }
         }
 
-        BitmapFontAtlas::new(self.metadata, self.image)
+        // The PNG image format indexes the image starting from the top left corner, so a
+        // bottom left atlas stores its rows in the opposite order.
+        let mut image = BitmapFontAtlasImage::new(data, width, height, self.format, Origin::TopLeft);
+        image.convert_origin(self.origin);
+
+        let (ascent, descent, line_gap) = self.line_metrics.unwrap_or((
+            ascent.unwrap_or(0.0),
+            f32::min(descent.unwrap_or(0.0), 0.0),
+            0.0
+        ));
+        let kerning = self.kerning.iter()
+            .map(|(&pair, &amount)| (pair, amount / slot))
+            .collect();
+        let metadata = BitmapFontAtlasMetadata {
+            origin: self.origin,
+            layout: AtlasLayout::Grid,
+            width: width,
+            height: height,
+            columns: columns,
+            rows: rows,
+            padding: padding,
+            slot_glyph_size: slot_glyph_size,
+            glyph_size: slot_glyph_size - padding,
+            font_size: self.font_size.unwrap_or((slot_glyph_size - padding) as f32),
+            ascent: ascent / slot,
+            descent: descent / slot,
+            line_gap: line_gap / slot,
+            page_count: 1,
+            glyph_metadata: glyph_metadata,
+            kerning: kerning,
+            distance_field: None,
+            notdef: self.notdef,
+        };
+
+        Ok(BitmapFontAtlas::new(metadata, vec![image]))
+    }
+}
+
+/// Flip the rows of an image buffer in place, turning a top-down image into a bottom-up
+/// image and vice versa.
+fn flip_vertically(data: &mut [u8], width_in_bytes: usize, height: usize) {
+    let half_height = height / 2;
+    for row in 0..half_height {
+        let (top, bottom) = data.split_at_mut((height - row - 1) * width_in_bytes);
+        top[row * width_in_bytes..(row + 1) * width_in_bytes].swap_with_slice(&mut bottom[..width_in_bytes]);
     }
 }
 
@@ -267,6 +1040,21 @@ pub enum ErrorKind {
     CannotLoadAtlasImage,
     FontMetadataNotFound,
     CannotLoadAtlasMetadata,
+    CannotLoadFont,
+    InvalidAtlasParameters,
+    CannotLoadBmFont,
+    InvalidAtlas,
+    CannotCreateFile,
+    CannotWriteMetadata,
+    CannotEncodeAtlasImage,
+    CannotFinalizeArchive,
+    ImageDimensionMismatch,
+    UnsupportedFormatVersion,
+    InvalidWriteOptions,
+    ArchiveEntryTooLarge,
+    ImageTooLarge,
+    TooManyGlyphs,
+    InvalidRenderTarget,
 }
 
 impl ErrorKind {
@@ -278,6 +1066,21 @@ impl ErrorKind {
             ErrorKind::CannotLoadAtlasImage => "The font atlas contains an atlas image but it cannot be loaded",
             ErrorKind::FontMetadataNotFound => "The font atlas contains no metadata",
             ErrorKind::CannotLoadAtlasMetadata => "The font atlas metadata is corrupt",
+            ErrorKind::CannotLoadFont => "The font file cannot be loaded",
+            ErrorKind::InvalidAtlasParameters => "The font atlas parameters are invalid",
+            ErrorKind::CannotLoadBmFont => "The BMFont descriptor is corrupt",
+            ErrorKind::InvalidAtlas => "The font atlas violates the invariants of the bmfa format",
+            ErrorKind::CannotCreateFile => "The file cannot be created",
+            ErrorKind::CannotWriteMetadata => "The font atlas metadata cannot be written",
+            ErrorKind::CannotEncodeAtlasImage => "The font atlas image cannot be encoded",
+            ErrorKind::CannotFinalizeArchive => "The font atlas archive cannot be finalized",
+            ErrorKind::ImageDimensionMismatch => "The font atlas image does not match the atlas dimensions",
+            ErrorKind::UnsupportedFormatVersion => "The font atlas was written in a newer, unsupported format version",
+            ErrorKind::InvalidWriteOptions => "The font atlas write options are invalid",
+            ErrorKind::ArchiveEntryTooLarge => "The font atlas archive contains an entry exceeding the read limits",
+            ErrorKind::ImageTooLarge => "The font atlas images exceed the read limits",
+            ErrorKind::TooManyGlyphs => "The font atlas contains more glyphs than the read limits allow",
+            ErrorKind::InvalidRenderTarget => "The render target does not match its dimensions",
         }
     }
 }
@@ -310,94 +1113,670 @@ impl Error {
     }
 }
 
-impl error::Error for Error {}
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&*self.repr.error)
+    }
+}
 
-/// Read in a bitmap font atlas from an external source.
-pub fn from_reader<R: io::Read + io::Seek>(reader: R) -> Result<BitmapFontAtlas, Error> {
-    let mut zip = zip::ZipArchive::new(reader).map_err(|e| {
-        Error::new(ErrorKind::FileExistsButCannotBeOpened, Box::new(e))
+/// Return the name of the archive entry storing an atlas page. Single page atlases store
+/// their image in `atlas.png`, and multiple page atlases store each page in `atlas_<page>.png`.
+fn page_file_name(page: usize, page_count: usize) -> String {
+    if page_count == 1 {
+        String::from("atlas.png")
+    } else {
+        format!("atlas_{}.png", page)
+    }
+}
+
+/// Open the archive entry storing an atlas page.
+fn open_page<'a, R: io::Read + io::Seek>(
+    zip: &'a mut zip::ZipArchive<R>, file_name: &str,
+    limits: &ReadLimits) -> Result<io::Take<zip::read::ZipFile<'a>>, Error> {
+
+    let atlas_file = zip.by_name(file_name).map_err(|e| {
+        Error::new(ErrorKind::FontAtlasImageNotFound, Box::new(e))
+    })?;
+
+    limit_entry(atlas_file, limits.max_image_entry_size)
+}
+
+/// Read the dimensions of an atlas page from its image header alone, never letting the
+/// decoder allocate more than `budget` bytes for a row of the image.
+fn read_page_dimensions<R: io::Read + io::Seek>(
+    zip: &mut zip::ZipArchive<R>, file_name: &str,
+    limits: &ReadLimits, budget: usize) -> Result<(usize, usize), Error> {
+
+    let atlas_file = open_page(zip, file_name, limits)?;
+    let mut decoder = ::png::Decoder::new_with_limits(atlas_file, ::png::Limits { bytes: budget });
+    decoder.set_transformations(::png::Transformations::EXPAND);
+    let (info, _) = decoder.read_info().map_err(|e| match e {
+        ::png::DecodingError::LimitsExceeded => Error::new(
+            ErrorKind::ImageTooLarge,
+            format!("decoding atlas image {} would exceed the decompressed size limit", file_name).into()
+        ),
+        e => Error::new(ErrorKind::CannotLoadAtlasImage, Box::new(e)),
     })?;
+
+    Ok((info.width as usize, info.height as usize))
+}
+
+/// Decode one atlas page from the archive.
+fn read_page<R: io::Read + io::Seek>(
+    zip: &mut zip::ZipArchive<R>, page: usize, page_count: usize, origin: Origin,
+    limits: &ReadLimits, budget: &mut usize) -> Result<BitmapFontAtlasImage, Error> {
+
+    let file_name = page_file_name(page, page_count);
+    // A single page atlas may also have been written with an indexed page name.
+    let file_name = if page_count == 1 && zip.by_name(&file_name).is_err() {
+        String::from("atlas_0.png")
+    } else {
+        file_name
+    };
+    // The image decoder allocates its buffers from the image header, and decodes an
+    // interlaced image in full, before it reports the dimensions. Check the header against
+    // the limits first.
+    let (width, height) = read_page_dimensions(zip, &file_name, limits, *budget)?;
+    if width > limits.max_image_dimension || height > limits.max_image_dimension {
+        return Err(Error::new(
+            ErrorKind::ImageTooLarge,
+            format!(
+                "atlas image {} is {}x{} pixels, but the limit is {} pixels on a side",
+                file_name, width, height, limits.max_image_dimension
+            ).into()
+        ));
+    }
+    let atlas_file = open_page(zip, &file_name, limits)?;
+    let png_reader = png::PngDecoder::new(atlas_file).map_err(|e| {
+        Error::new(ErrorKind::CannotLoadAtlasImage, Box::new(e))
+    })?;
+    let format = PixelFormat::from_color_type(png_reader.color_type()).ok_or_else(|| {
+        Error::new(ErrorKind::CannotLoadAtlasImage, "the atlas image has an unsupported color type".into())
+    })?;
+    // Check the size of the decoded image against what remains of the budget before
+    // allocating anything for it.
+    let len_bytes = width.checked_mul(height)
+        .and_then(|pixels| pixels.checked_mul(format.bytes_per_pixel()))
+        .filter(|&len_bytes| len_bytes <= *budget)
+        .ok_or_else(|| {
+            Error::new(
+                ErrorKind::ImageTooLarge,
+                format!("decoding atlas image {} would exceed the decompressed size limit", file_name).into()
+            )
+        })?;
+    *budget -= len_bytes;
+    let mut image: Vec<u8> = vec![0; len_bytes];
+    png_reader.read_image(&mut image).map_err(|e| {
+        Error::new(ErrorKind::CannotLoadAtlasImage, Box::new(e))
+    })?;
+    // If the origin is declared as the bottom left, we must flip the image since the
+    // PNG image format indexes the image starting from the top left corner
+    // going right and downwards.
+    let mut image = BitmapFontAtlasImage::new(image, width, height, format, Origin::TopLeft);
+    image.convert_origin(origin);
+
+    Ok(image)
+}
+
+/// The version of the metadata format written by this library. Metadata without a
+/// version number predates versioning and is version 1.
+pub const FORMAT_VERSION: u64 = 2;
+
+/// A `Migration` upgrades the metadata of one format version to the next version.
+type Migration = fn(&mut serde_json::Value);
+
+/// The migrations between consecutive format versions. The migration at index `i` upgrades
+/// version `i + 1` to version `i + 2`.
+const MIGRATIONS: [Migration; FORMAT_VERSION as usize - 1] = [
+    fill_missing_metrics,
+];
+
+/// The metadata as it is stored in the archive, tagged with its format version.
+#[derive(Serialize)]
+struct VersionedMetadata<'a> {
+    version: u64,
+    #[serde(flatten)]
+    metadata: &'a BitmapFontAtlasMetadata,
+}
+
+/// Upgrade metadata of any supported format version to the current format version.
+fn migrate(metadata: &mut serde_json::Value) -> Result<(), Error> {
+    let version = match metadata.get("version") {
+        None => 1,
+        Some(version) => version.as_u64().filter(|&version| version > 0).ok_or_else(|| {
+            Error::new(ErrorKind::CannotLoadAtlasMetadata, "the format version is invalid".into())
+        })?,
+    };
+    if version > FORMAT_VERSION {
+        return Err(Error::new(
+            ErrorKind::UnsupportedFormatVersion,
+            format!("format version {} is newer than the supported version {}", version, FORMAT_VERSION).into()
+        ));
+    }
+
+    for migration in MIGRATIONS[((version - 1) as usize)..].iter() {
+        migration(metadata);
+    }
+
+    Ok(())
+}
+
+/// Fill in the glyph advances and line metrics missing from metadata written before the
+/// atlas recorded them. The advance of a glyph defaults to its width, the ascent and
+/// descent default to the extent of the visible glyphs around the baseline, the line gap
+/// defaults to zero, and the font size defaults to the glyph size.
+fn fill_missing_metrics(metadata: &mut serde_json::Value) {
+    use serde_json::Value;
+
+    let metadata = match metadata.as_object_mut() {
+        Some(metadata) => metadata,
+        None => return,
+    };
+
+    let mut ascent: Option<f64> = None;
+    let mut descent: Option<f64> = None;
+    if let Some(Value::Object(glyph_metadata)) = metadata.get_mut("glyph_metadata") {
+        for glyph in glyph_metadata.values_mut().filter_map(|glyph| glyph.as_object_mut()) {
+            let width = glyph.get("width").and_then(|width| width.as_f64()).unwrap_or(0.0);
+            let height = glyph.get("height").and_then(|height| height.as_f64()).unwrap_or(0.0);
+            let y_offset = glyph.get("y_offset").and_then(|y_offset| y_offset.as_f64()).unwrap_or(0.0);
+            let is_whitespace = glyph.get("code_point")
+                .and_then(|code_point| code_point.as_u64())
+                .and_then(|code_point| std::char::from_u32(code_point as u32))
+                .is_some_and(|ch| ch.is_whitespace());
+            if !is_whitespace && height > 0.0 {
+                ascent = Some(ascent.map_or(y_offset + height, |ascent| f64::max(ascent, y_offset + height)));
+                descent = Some(descent.map_or(y_offset, |descent| f64::min(descent, y_offset)));
+            }
+            if !glyph.contains_key("advance") {
+                glyph.insert(String::from("advance"), Value::from(width));
+            }
+        }
+    }
+
+    let glyph_size = metadata.get("glyph_size").and_then(|glyph_size| glyph_size.as_f64()).unwrap_or(0.0);
+    let defaults = [
+        ("font_size", glyph_size),
+        ("ascent", ascent.unwrap_or(0.0)),
+        ("descent", f64::min(descent.unwrap_or(0.0), 0.0)),
+        ("line_gap", 0.0),
+    ];
+    for &(key, default) in defaults.iter() {
+        if !metadata.contains_key(key) {
+            metadata.insert(String::from(key), Value::from(default));
+        }
+    }
+}
+
+/// The `ReadLimits` struct bounds the resources reading in a bitmap font atlas may
+/// consume, so that a corrupt or malicious archive yields an error instead of an
+/// enormous allocation.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct ReadLimits {
+    /// The largest width or height of an atlas image, in pixels.
+    pub max_image_dimension: usize,
+    /// The largest number of bytes all of the decoded atlas images may occupy together.
+    pub max_decompressed_size: usize,
+    /// The largest number of glyphs in the glyph table.
+    pub max_glyph_count: usize,
+    /// The largest uncompressed size of the metadata file, in bytes.
+    pub max_metadata_size: usize,
+    /// The largest uncompressed size of the file of one atlas image, in bytes.
+    pub max_image_entry_size: usize,
+}
+
+impl ReadLimits {
+    /// Construct read limits that comfortably admit any reasonable atlas.
+    pub fn new() -> ReadLimits {
+        ReadLimits {
+            max_image_dimension: 16384,
+            max_decompressed_size: 1 << 30,
+            max_glyph_count: 0x110000,
+            max_metadata_size: 64 << 20,
+            max_image_entry_size: 1 << 30,
+        }
+    }
+
+    /// Construct read limits that admit any atlas at all.
+    pub fn unlimited() -> ReadLimits {
+        ReadLimits {
+            max_image_dimension: usize::MAX,
+            max_decompressed_size: usize::MAX,
+            max_glyph_count: usize::MAX,
+            max_metadata_size: usize::MAX,
+            max_image_entry_size: usize::MAX,
+        }
+    }
+}
+
+impl Default for ReadLimits {
+    fn default() -> ReadLimits {
+        ReadLimits::new()
+    }
+}
+
+/// The `ReadOptions` struct holds the parameters that control how a bitmap font atlas
+/// gets read in.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct ReadOptions {
+    /// Whether to reject atlases that violate the invariants of the bmfa format. When
+    /// unset, an invalid atlas loads anyway and can be checked with
+    /// [`BitmapFontAtlas::validate`].
+    pub strict: bool,
+    /// The resource limits enforced while reading.
+    pub limits: ReadLimits,
+}
+
+impl ReadOptions {
+    /// Construct read options that accept any atlas that can be decoded within the
+    /// default read limits.
+    pub fn new() -> ReadOptions {
+        ReadOptions {
+            strict: false,
+            limits: ReadLimits::new(),
+        }
+    }
+}
+
+impl Default for ReadOptions {
+    fn default() -> ReadOptions {
+        ReadOptions::new()
+    }
+}
+
+/// Reject an atlas for which validation reported any diagnostics.
+pub(crate) fn reject_diagnostics(diagnostics: &[validate::Diagnostic]) -> Result<(), Error> {
+    if diagnostics.is_empty() {
+        return Ok(());
+    }
+    let messages: Vec<String> = diagnostics.iter().map(|diagnostic| diagnostic.to_string()).collect();
+
+    Err(Error::new(ErrorKind::InvalidAtlas, messages.join("; ").into()))
+}
+
+/// Open the zip archive of a bitmap font atlas.
+fn open_archive<R: io::Read + io::Seek>(reader: R) -> Result<zip::ZipArchive<R>, Error> {
+    zip::ZipArchive::new(reader).map_err(|e| {
+        Error::new(ErrorKind::FileExistsButCannotBeOpened, Box::new(e))
+    })
+}
+
+/// Check the uncompressed size of an archive entry against a read limit, and bound the
+/// entry to one byte past the limit in case its declared size is wrong.
+fn limit_entry(entry: zip::read::ZipFile<'_>, max_size: usize) -> Result<io::Take<zip::read::ZipFile<'_>>, Error> {
+    if entry.size() > max_size as u64 {
+        return Err(Error::new(
+            ErrorKind::ArchiveEntryTooLarge,
+            format!("{} is {} bytes, but the limit is {} bytes", entry.name(), entry.size(), max_size).into()
+        ));
+    }
+
+    Ok(io::Read::take(entry, (max_size as u64).saturating_add(1)))
+}
+
+/// Read the metadata from the archive of a bitmap font atlas, upgrading it to the current
+/// format version.
+fn read_metadata<R: io::Read + io::Seek>(
+    zip: &mut zip::ZipArchive<R>, limits: &ReadLimits) -> Result<BitmapFontAtlasMetadata, Error> {
+
+    let entry_count = zip.len();
     let metadata_file = zip.by_name("metadata.json").map_err(|e| {
         Error::new(ErrorKind::FontMetadataNotFound, Box::new(e))
     })?;
-    let metadata: BitmapFontAtlasMetadata = serde_json::from_reader(metadata_file).map_err(|e| {
+    let mut metadata_file = limit_entry(metadata_file, limits.max_metadata_size)?;
+    let mut metadata_bytes = vec![];
+    io::Read::read_to_end(&mut metadata_file, &mut metadata_bytes).map_err(|e| {
         Error::new(ErrorKind::CannotLoadAtlasMetadata, Box::new(e))
     })?;
-    let atlas_file = zip.by_name("atlas.png").map_err(|e| {
-        Error::new(ErrorKind::FontAtlasImageNotFound, Box::new(e))
+    if metadata_bytes.len() > limits.max_metadata_size {
+        return Err(Error::new(
+            ErrorKind::ArchiveEntryTooLarge,
+            format!("metadata.json is larger than the limit of {} bytes", limits.max_metadata_size).into()
+        ));
+    }
+    // Parsing the metadata allocates for every glyph, so count the glyphs first.
+    let glyph_count = glyph_table::count(&metadata_bytes).map_err(|e| {
+        Error::new(ErrorKind::CannotLoadAtlasMetadata, Box::new(e))
     })?;
-    let png_reader = png::PngDecoder::new(atlas_file).map_err(|e| {
-        Error::new(ErrorKind::CannotLoadAtlasImage, Box::new(e))
+    if glyph_count > limits.max_glyph_count {
+        return Err(Error::new(
+            ErrorKind::TooManyGlyphs,
+            format!(
+                "the font atlas has {} glyphs, but the limit is {}",
+                glyph_count, limits.max_glyph_count
+            ).into()
+        ));
+    }
+    let mut metadata_json: serde_json::Value = serde_json::from_slice(&metadata_bytes).map_err(|e| {
+        Error::new(ErrorKind::CannotLoadAtlasMetadata, Box::new(e))
     })?;
-    let (width, height) = png_reader.dimensions();
-    let (width, height) = (width as usize, height as usize);
-    let depth = png_reader.color_type().bytes_per_pixel() as usize;
-    let mut image: Vec<u8> = vec![0; width * height * depth];
-    png_reader.read_image(&mut image).map_err(|e| {
-        Error::new(ErrorKind::CannotLoadAtlasImage, Box::new(e))
+    migrate(&mut metadata_json)?;
+
+    let metadata: BitmapFontAtlasMetadata = serde_json::from_value(metadata_json).map_err(|e| {
+        Error::new(ErrorKind::CannotLoadAtlasMetadata, Box::new(e))
+    })?;
+    // Every page is a separate entry of the archive, so there cannot be more pages than
+    // entries.
+    if metadata.page_count > entry_count {
+        return Err(Error::new(
+            ErrorKind::FontAtlasImageNotFound,
+            format!("the font atlas declares {} pages, but the archive has {} entries", metadata.page_count, entry_count).into()
+        ));
+    }
+
+    Ok(metadata)
+}
+
+/// Read in only the metadata of a bitmap font atlas from an external source, without
+/// decoding the atlas images.
+pub fn metadata_from_reader<R: io::Read + io::Seek>(reader: R) -> Result<BitmapFontAtlasMetadata, Error> {
+    metadata_from_reader_with_options(reader, &ReadOptions::new())
+}
+
+/// Read in only the metadata of a bitmap font atlas from an external source with the given
+/// options, without decoding the atlas images. In strict mode only the metadata is
+/// validated.
+pub fn metadata_from_reader_with_options<R: io::Read + io::Seek>(
+    reader: R, options: &ReadOptions) -> Result<BitmapFontAtlasMetadata, Error> {
+
+    let mut zip = open_archive(reader)?;
+    let metadata = read_metadata(&mut zip, &options.limits)?;
+    if options.strict {
+        reject_diagnostics(&metadata.validate())?;
+    }
+
+    Ok(metadata)
+}
+
+/// Load only the metadata of a bitmap font atlas from a file, without decoding the
+/// atlas images.
+pub fn load_metadata<P: AsRef<Path>>(path: P) -> Result<BitmapFontAtlasMetadata, Error> {
+    load_metadata_with_options(path, &ReadOptions::new())
+}
+
+/// Load only the metadata of a bitmap font atlas from a file with the given options,
+/// without decoding the atlas images.
+pub fn load_metadata_with_options<P: AsRef<Path>>(path: P, options: &ReadOptions) -> Result<BitmapFontAtlasMetadata, Error> {
+    let reader = File::open(&path).map_err(|e| {
+        Error::new(ErrorKind::FileNotFound, Box::new(e))
     })?;
-    let atlas_image = BitmapFontAtlasImage::new(
-        image, width as usize, height as usize, metadata.origin
-    );
-    let builder = BitmapFontAtlasBuilder::new(metadata, atlas_image);
 
-    Ok(builder.build())
+    metadata_from_reader_with_options(reader, options)
+}
+
+/// Read in a bitmap font atlas from an external source.
+pub fn from_reader<R: io::Read + io::Seek>(reader: R) -> Result<BitmapFontAtlas, Error> {
+    from_reader_with_options(reader, &ReadOptions::new())
+}
+
+/// Read in a bitmap font atlas from an external source with the given options.
+pub fn from_reader_with_options<R: io::Read + io::Seek>(reader: R, options: &ReadOptions) -> Result<BitmapFontAtlas, Error> {
+    let mut zip = open_archive(reader)?;
+    let metadata = read_metadata(&mut zip, &options.limits)?;
+    if metadata.page_count == 0 {
+        return Err(Error::new(ErrorKind::FontAtlasImageNotFound, "the font atlas has no pages".into()));
+    }
+    let mut budget = options.limits.max_decompressed_size;
+    let mut pages = vec![];
+    for page in 0..metadata.page_count {
+        pages.push(read_page(&mut zip, page, metadata.page_count, metadata.origin, &options.limits, &mut budget)?);
+    }
+    let atlas = BitmapFontAtlas::new(metadata, pages);
+    if options.strict {
+        reject_diagnostics(&atlas.validate())?;
+    }
+
+    Ok(atlas)
 }
 
 /// Load a bitmap font atlas directly from a file.
 pub fn load<P: AsRef<Path>>(path: P) -> Result<BitmapFontAtlas, Error> {
+    load_with_options(path, &ReadOptions::new())
+}
+
+/// Load a bitmap font atlas directly from a file with the given options.
+pub fn load_with_options<P: AsRef<Path>>(path: P, options: &ReadOptions) -> Result<BitmapFontAtlas, Error> {
     let reader = File::open(&path).map_err(|e| {
         Error::new(ErrorKind::FileNotFound, Box::new(e))
     })?;
 
-    from_reader(reader)
+    from_reader_with_options(reader, options)
+}
+
+/// Check that the atlas has at least one page, and that every page matches the dimensions
+/// of the atlas before writing it out.
+pub(crate) fn check_page_dimensions(atlas: &BitmapFontAtlas) -> Result<(), Error> {
+    if atlas.pages.is_empty() {
+        return Err(Error::new(ErrorKind::InvalidAtlasParameters, "the atlas has no pages".into()));
+    }
+    for (page, image) in atlas.pages.iter().enumerate() {
+        // The dimensions of a page built by hand may overflow.
+        let expected_len_bytes = image.checked_len_bytes();
+        if image.width != atlas.width || image.height != atlas.height || Some(image.data.len()) != expected_len_bytes {
+            return Err(Error::new(
+                ErrorKind::ImageDimensionMismatch,
+                format!(
+                    "page {} is {}x{} pixels stored in {} bytes, but the atlas is {}x{} pixels",
+                    page, image.width, image.height, image.data.len(), atlas.width, atlas.height
+                ).into()
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// The `ArchiveCompression` parameter selects how the entries of the zip archive of a
+/// bitmap font atlas are compressed.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ArchiveCompression {
+    /// The entries are stored without compression.
+    Stored,
+    /// The entries are compressed with the deflate algorithm.
+    Deflated,
+}
+
+/// The `PngCompression` parameter selects how hard the PNG encoder works to compress the
+/// atlas images.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum PngCompression {
+    /// The default compression level of the encoder.
+    Default,
+    /// Fast, minimal compression.
+    Fast,
+    /// The highest compression level.
+    Best,
+}
+
+/// The `PngFilter` parameter selects the filter the PNG encoder applies to each row of
+/// the atlas images before compressing them.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum PngFilter {
+    /// The rows are compressed as they are.
+    NoFilter,
+    /// Each pixel is stored relative to the pixel to its left.
+    Sub,
+    /// Each pixel is stored relative to the pixel above it.
+    Up,
+    /// Each pixel is stored relative to the average of the pixels to its left and above it.
+    Avg,
+    /// Each pixel is stored relative to a prediction from the pixels to its left, above it,
+    /// and to its upper left.
+    Paeth,
+}
+
+/// The `WriteOptions` struct holds the parameters that control how a bitmap font atlas
+/// gets written out.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct WriteOptions {
+    /// The compression method of the zip archive entries.
+    pub archive_compression: ArchiveCompression,
+    /// The compression level of the zip archive entries. Deflated entries accept levels
+    /// from 0 to 9, and stored entries accept no level. When unset, the default level of
+    /// the compression method is used.
+    pub compression_level: Option<i32>,
+    /// The compression level of the atlas images.
+    pub png_compression: PngCompression,
+    /// The row filter of the atlas images.
+    pub png_filter: PngFilter,
+    /// Whether to indent the metadata for readability instead of writing it compactly.
+    pub pretty_json: bool,
+}
+
+impl WriteOptions {
+    /// Construct write options that store the archive entries without compression, write
+    /// the atlas images with fast compression, and indent the metadata.
+    pub fn new() -> WriteOptions {
+        WriteOptions {
+            archive_compression: ArchiveCompression::Stored,
+            compression_level: None,
+            png_compression: PngCompression::Fast,
+            png_filter: PngFilter::Sub,
+            pretty_json: true,
+        }
+    }
+
+    /// Check that the compression level is supported by the compression method.
+    fn validate(&self) -> Result<(), Error> {
+        let is_valid = match (self.archive_compression, self.compression_level) {
+            (_, None) => true,
+            (ArchiveCompression::Stored, Some(_)) => false,
+            (ArchiveCompression::Deflated, Some(level)) => (0..=9).contains(&level),
+        };
+        if !is_valid {
+            return Err(Error::new(
+                ErrorKind::InvalidWriteOptions,
+                format!(
+                    "compression level {:?} is not supported by {:?} compression",
+                    self.compression_level, self.archive_compression
+                ).into()
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Return the options of the zip archive entries.
+    fn file_options(&self) -> zip::write::FileOptions {
+        let compression_method = match self.archive_compression {
+            ArchiveCompression::Stored => zip::CompressionMethod::Stored,
+            ArchiveCompression::Deflated => zip::CompressionMethod::Deflated,
+        };
+
+        // Fix the modification time of every entry so the archive only depends on the atlas.
+        zip::write::FileOptions::default()
+            .compression_method(compression_method)
+            .compression_level(self.compression_level)
+            .last_modified_time(zip::DateTime::default())
+            .unix_permissions(0o644)
+    }
+
+    /// Construct a PNG encoder with the image options.
+    fn png_encoder<W: io::Write>(&self, writer: W) -> png::PngEncoder<W> {
+        let compression = match self.png_compression {
+            PngCompression::Default => png::CompressionType::Default,
+            PngCompression::Fast => png::CompressionType::Fast,
+            PngCompression::Best => png::CompressionType::Best,
+        };
+        let filter = match self.png_filter {
+            PngFilter::NoFilter => png::FilterType::NoFilter,
+            PngFilter::Sub => png::FilterType::Sub,
+            PngFilter::Up => png::FilterType::Up,
+            PngFilter::Avg => png::FilterType::Avg,
+            PngFilter::Paeth => png::FilterType::Paeth,
+        };
+
+        png::PngEncoder::new_with_quality(writer, compression, filter)
+    }
+}
+
+impl Default for WriteOptions {
+    fn default() -> WriteOptions {
+        WriteOptions::new()
+    }
+}
+
+/// Encode an atlas page as a PNG image. PNG stores the rows of an image from the top down,
+/// so a page with a bottom left origin gets flipped over in a copy before it is encoded.
+pub(crate) fn encode_page<E: ImageEncoder>(encoder: E, page: &BitmapFontAtlasImage) -> Result<(), Error> {
+    let image = match page.origin {
+        Origin::TopLeft => Cow::Borrowed(page),
+        Origin::BottomLeft => {
+            let mut image = page.clone();
+            image.convert_origin(Origin::TopLeft);
+            Cow::Owned(image)
+        }
+    };
+
+    // PNG stores 16-bit channels big endian, which `write_image` converts them to.
+    encoder.write_image(
+        &image.data, image.width as u32, image.height as u32, image.format.color_type()
+    ).map_err(|e| {
+        Error::new(ErrorKind::CannotEncodeAtlasImage, Box::new(e))
+    })
 }
 
 /// Write out of bitmap font atlas to a writer or buffer.
-pub fn to_writer<W: io::Write + io::Seek>(writer: W, atlas: &BitmapFontAtlas) -> io::Result<()> {
+pub fn to_writer<W: io::Write + io::Seek>(writer: W, atlas: &BitmapFontAtlas) -> Result<(), Error> {
+    to_writer_with_options(writer, atlas, &WriteOptions::new())
+}
+
+/// Write out of bitmap font atlas to a writer or buffer with the given options.
+pub fn to_writer_with_options<W: io::Write + io::Seek>(
+    writer: W, atlas: &BitmapFontAtlas, options: &WriteOptions) -> Result<(), Error> {
+
+    options.validate()?;
+    check_page_dimensions(atlas)?;
+
     let mut zip_file = zip::ZipWriter::new(writer);
-    let options =
-        zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+    let file_options = options.file_options();
 
     // Write out the metadata.
-    zip_file.start_file("metadata.json", options)?;
-    serde_json::to_writer_pretty(&mut zip_file, &atlas.metadata())?;
-
-    // if the origin is the bottom left of the image, we need to flip the image back over
-    // before writing it out.
-    let mut image = atlas.image.clone();
-    if image.origin == Origin::BottomLeft {
-        let height = image.height;
-        let width_in_bytes = 4 * image.width;
-        let half_height = image.height / 2;
-        for row in 0..half_height {
-            for col in 0..width_in_bytes {
-                let temp = image.data[row * width_in_bytes + col];
-                image.data[row * width_in_bytes + col] = image.data[((height - row - 1) * width_in_bytes) + col];
-                image.data[((height - row - 1) * width_in_bytes) + col] = temp;
-            }
-        }
-    }
+    zip_file.start_file("metadata.json", file_options).map_err(|e| {
+        Error::new(ErrorKind::CannotWriteMetadata, Box::new(e))
+    })?;
+    let metadata = atlas.metadata();
+    let versioned_metadata = VersionedMetadata { version: FORMAT_VERSION, metadata: &metadata };
+    let result = if options.pretty_json {
+        serde_json::to_writer_pretty(&mut zip_file, &versioned_metadata)
+    } else {
+        serde_json::to_writer(&mut zip_file, &versioned_metadata)
+    };
+    result.map_err(|e| {
+        Error::new(ErrorKind::CannotWriteMetadata, Box::new(e))
+    })?;
 
-    // Write out the atlas image.
-    zip_file.start_file("atlas.png", options)?;
-    let png_writer = png::PNGEncoder::new(&mut zip_file);
-    png_writer.encode(
-        image.as_ref(), atlas.width as u32, atlas.height as u32, ColorType::Rgba8
-    ).map_err(|e| io::Error::new(io::ErrorKind::Other, Box::new(e)))?;
+    for (page, atlas_image) in atlas.pages.iter().enumerate() {
+        // Write out the atlas image.
+        zip_file.start_file(page_file_name(page, atlas.pages.len()), file_options).map_err(|e| {
+            Error::new(ErrorKind::CannotEncodeAtlasImage, Box::new(e))
+        })?;
+        encode_page(options.png_encoder(&mut zip_file), atlas_image)?;
+    }
 
-    zip_file.finish()?;
+    zip_file.finish().map_err(|e| {
+        Error::new(ErrorKind::CannotFinalizeArchive, Box::new(e))
+    })?;
 
     Ok(())
 }
 
 /// Write the bitmap font atlas to a file.
-pub fn write_to_file<P: AsRef<Path>>(path: P, atlas: &BitmapFontAtlas) -> io::Result<()> {
+pub fn write_to_file<P: AsRef<Path>>(path: P, atlas: &BitmapFontAtlas) -> Result<(), Error> {
+    write_to_file_with_options(path, atlas, &WriteOptions::new())
+}
+
+/// Write the bitmap font atlas to a file with the given options.
+pub fn write_to_file_with_options<P: AsRef<Path>>(
+    path: P, atlas: &BitmapFontAtlas, options: &WriteOptions) -> Result<(), Error> {
+
     // Set up the image zip archive.
     let mut file_path = path.as_ref().to_path_buf();
     file_path.set_extension("bmfa");
-    let file = File::create(&file_path)?;
+    let file = File::create(&file_path).map_err(|e| {
+        Error::new(ErrorKind::CannotCreateFile, Box::new(e))
+    })?;
 
     // Write out the atlas contents.
-    to_writer(file, atlas)
+    to_writer_with_options(file, atlas, options)
 }
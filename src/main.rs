@@ -0,0 +1,235 @@
+use bmfa::{BitmapFontAtlas, GlyphMetadata};
+use std::env;
+use std::error;
+use std::fs;
+use std::io;
+use std::io::Write;
+use std::path::Path;
+use std::process;
+
+
+const USAGE: &str = "\
+Usage: bmfa <command> [<arguments>]
+
+Commands:
+    info <atlas.bmfa>                               Show the parameters of an atlas
+    glyphs <atlas.bmfa>                             Print the glyph table of an atlas
+    validate <atlas.bmfa>                           Check an atlas against the bmfa format
+    extract <atlas.bmfa> <directory>                Write out the metadata and images of an atlas
+    pack <atlas.bmfa> <metadata.json> <page.png>... Build an atlas from metadata and images
+    help                                            Show this message
+";
+
+/// The exit status of a command that ran, but found a problem with its input.
+const EXIT_INVALID: i32 = 1;
+/// The exit status of a command that could not run.
+const EXIT_ERROR: i32 = 2;
+
+type Result<T> = std::result::Result<T, Box<dyn error::Error>>;
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match run(&args) {
+        Ok(status) => process::exit(status),
+        // The output was closed early, for instance by piping it into `head`.
+        Err(e) if e.downcast_ref::<io::Error>().is_some_and(|e| e.kind() == io::ErrorKind::BrokenPipe) => {}
+        Err(e) => {
+            eprintln!("bmfa: {}", e);
+            process::exit(EXIT_ERROR);
+        }
+    }
+}
+
+/// Run the command named by the first argument, returning the exit status.
+fn run(args: &[String]) -> Result<i32> {
+    let command = match args.first() {
+        Some(command) => command.as_str(),
+        None => {
+            eprint!("{}", USAGE);
+            return Ok(EXIT_ERROR);
+        }
+    };
+    let operands = &args[1..];
+    match command {
+        "info" => info(&operand(operands, 0, command)?),
+        "glyphs" => glyphs(&operand(operands, 0, command)?),
+        "validate" => validate(&operand(operands, 0, command)?),
+        "extract" => extract(&operand(operands, 0, command)?, &operand(operands, 1, command)?),
+        "pack" => {
+            if operands.len() < 3 {
+                return Err(format!("pack needs an output file, a metadata file and at least one page\n\n{}", USAGE).into());
+            }
+            pack(&operands[0], &operands[1], &operands[2..])
+        }
+        "help" | "-h" | "--help" => {
+            print!("{}", USAGE);
+            Ok(0)
+        }
+        "-V" | "--version" => {
+            println!("bmfa {}", env!("CARGO_PKG_VERSION"));
+            Ok(0)
+        }
+        _ => Err(format!("unknown command `{}`\n\n{}", command, USAGE).into()),
+    }
+}
+
+/// Return one operand of a command, failing with the usage message when it is missing.
+fn operand(operands: &[String], index: usize, command: &str) -> Result<String> {
+    operands.get(index).cloned().ok_or_else(|| {
+        format!("{} is missing an argument\n\n{}", command, USAGE).into()
+    })
+}
+
+/// Format a list of code points as a list of ranges of consecutive code points.
+fn coverage(code_points: &[usize]) -> String {
+    let mut ranges = vec![];
+    let mut i = 0;
+    while i < code_points.len() {
+        let start = code_points[i];
+        while i + 1 < code_points.len() && code_points[i + 1] == code_points[i] + 1 {
+            i += 1;
+        }
+        let end = code_points[i];
+        if start == end {
+            ranges.push(format!("U+{:04X}", start));
+        } else {
+            ranges.push(format!("U+{:04X}-U+{:04X}", start, end));
+        }
+        i += 1;
+    }
+
+    ranges.join(", ")
+}
+
+/// Return the glyphs of an atlas in code point order.
+fn sorted_glyphs(atlas: &BitmapFontAtlas) -> Vec<&GlyphMetadata> {
+    let mut glyphs: Vec<&GlyphMetadata> = atlas.glyph_metadata.values().collect();
+    glyphs.sort_by_key(|glyph| glyph.code_point);
+
+    glyphs
+}
+
+fn info(path: &str) -> Result<i32> {
+    let atlas = bmfa::load(path)?;
+    let code_points: Vec<usize> = sorted_glyphs(&atlas).iter().map(|glyph| glyph.code_point).collect();
+    let format = atlas.pages.first().map_or(String::from("none"), |page| format!("{:?}", page.format()));
+    let distance_field = atlas.distance_field.map_or(String::from("none"), |distance_field| {
+        format!("range of {} pixels", distance_field.range)
+    });
+
+    println!("file:            {}", path);
+    println!("dimensions:      {}x{}", atlas.width, atlas.height);
+    println!("pages:           {}", atlas.pages.len());
+    println!("format:          {}", format);
+    println!("origin:          {:?}", atlas.origin);
+    println!("layout:          {:?}", atlas.layout);
+    println!("grid:            {} columns, {} rows", atlas.columns, atlas.rows);
+    println!("slot glyph size: {}", atlas.slot_glyph_size);
+    println!("glyph size:      {}", atlas.glyph_size);
+    println!("padding:         {}", atlas.padding);
+    println!("font size:       {}", atlas.font_size);
+    println!("ascent:          {}", atlas.ascent);
+    println!("descent:         {}", atlas.descent);
+    println!("line gap:        {}", atlas.line_gap);
+    println!("distance field:  {}", distance_field);
+    println!("glyphs:          {}", code_points.len());
+    println!("kerning pairs:   {}", atlas.kerning.len());
+    println!("notdef:          {}", atlas.notdef.map_or(String::from("none"), |notdef| format!("U+{:04X}", notdef)));
+    println!("coverage:        {}", coverage(&code_points));
+
+    Ok(0)
+}
+
+fn glyphs(path: &str) -> Result<i32> {
+    let atlas = bmfa::load(path)?;
+    let stdout = io::stdout();
+    let mut out = io::BufWriter::new(stdout.lock());
+    writeln!(out, "code_point\tchar\tpage\trow\tcolumn\trect\tx_min\ty_min\twidth\theight\tx_offset\ty_offset\tadvance")?;
+    for glyph in sorted_glyphs(&atlas) {
+        let ch = std::char::from_u32(glyph.code_point as u32)
+            .filter(|ch| !ch.is_control() && !ch.is_whitespace())
+            .map_or(String::new(), |ch| ch.to_string());
+        let rect = glyph.rect.map_or(String::from("-"), |rect| {
+            format!("{},{},{}x{}", rect.x, rect.y, rect.width, rect.height)
+        });
+        writeln!(
+            out, "U+{:04X}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            glyph.code_point, ch, glyph.page, glyph.row, glyph.column, rect,
+            glyph.x_min, glyph.y_min, glyph.width, glyph.height, glyph.x_offset, glyph.y_offset, glyph.advance
+        )?;
+    }
+    out.flush()?;
+
+    Ok(0)
+}
+
+fn validate(path: &str) -> Result<i32> {
+    let atlas = bmfa::load(path)?;
+    let diagnostics = atlas.validate();
+    if diagnostics.is_empty() {
+        println!("{}: ok", path);
+        return Ok(0);
+    }
+    for diagnostic in diagnostics.iter() {
+        println!("{}: {}", path, diagnostic);
+    }
+
+    Ok(EXIT_INVALID)
+}
+
+/// Write out the files of an atlas as this library writes them, so the metadata is in the
+/// current format version and the images are in PNG orientation.
+fn extract(path: &str, directory: &str) -> Result<i32> {
+    let atlas = bmfa::load(path)?;
+    let mut archive = io::Cursor::new(vec![]);
+    bmfa::to_writer(&mut archive, &atlas)?;
+    let mut zip = zip::ZipArchive::new(archive)?;
+
+    fs::create_dir_all(directory)?;
+    for i in 0..zip.len() {
+        let mut entry = zip.by_index(i)?;
+        let file_path = Path::new(directory).join(entry.name());
+        let mut file = fs::File::create(&file_path)?;
+        io::copy(&mut entry, &mut file)?;
+        println!("{}", file_path.display());
+    }
+
+    Ok(0)
+}
+
+/// Build an atlas from a metadata file and its pages in page order. The atlas is read
+/// back in before it is written out, so the files must form a loadable atlas.
+fn pack(path: &str, metadata_path: &str, page_paths: &[String]) -> Result<i32> {
+    let mut archive = zip::ZipWriter::new(io::Cursor::new(vec![]));
+    let options = zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+    archive.start_file("metadata.json", options)?;
+    archive.write_all(&fs::read(metadata_path)?)?;
+    for (page, page_path) in page_paths.iter().enumerate() {
+        let file_name = if page_paths.len() == 1 {
+            String::from("atlas.png")
+        } else {
+            format!("atlas_{}.png", page)
+        };
+        archive.start_file(file_name, options)?;
+        archive.write_all(&fs::read(page_path)?)?;
+    }
+    let mut archive = archive.finish()?;
+
+    // Check the metadata under the same limits the atlas is read with below.
+    let read_options = bmfa::ReadOptions::new();
+    archive.set_position(0);
+    let metadata = bmfa::metadata_from_reader_with_options(&mut archive, &read_options)?;
+    if metadata.page_count != page_paths.len() {
+        return Err(format!(
+            "{} declares {} pages, but {} were given", metadata_path, metadata.page_count, page_paths.len()
+        ).into());
+    }
+    archive.set_position(0);
+    let atlas = bmfa::from_reader_with_options(archive, &read_options)?;
+    // The atlas is written with a `.bmfa` extension whatever the extension of the path.
+    let file_path = Path::new(path).with_extension("bmfa");
+    bmfa::write_to_file(&file_path, &atlas)?;
+    println!("{}", file_path.display());
+
+    Ok(0)
+}
@@ -0,0 +1,198 @@
+use crate::layout::{self, LayoutSettings, PositionedGlyph};
+use crate::{BitmapFontAtlas, Origin};
+use std::convert::TryFrom;
+
+
+/// A `VertexAttribute` is one attribute of the vertices of a mesh. Every attribute is stored
+/// as one or more consecutive 32-bit floats.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum VertexAttribute {
+    /// The position of the vertex, as two floats `x` and `y`.
+    Position,
+    /// The texture coordinates of the vertex in its atlas page, as two floats `u` and `v`.
+    TexCoord,
+    /// The color of the vertex, as four floats `r`, `g`, `b` and `a`.
+    Color,
+    /// The atlas page the vertex samples, as one float, for drawing every page at once from
+    /// an array texture.
+    Page,
+}
+
+impl VertexAttribute {
+    /// Return the number of floats the attribute occupies in a vertex.
+    pub fn component_count(self) -> usize {
+        match self {
+            VertexAttribute::Position => 2,
+            VertexAttribute::TexCoord => 2,
+            VertexAttribute::Color => 4,
+            VertexAttribute::Page => 1,
+        }
+    }
+}
+
+/// The `Winding` parameter determines the order the corners of every triangle of a mesh
+/// come in, as seen with the text upright on the screen. Graphics APIs use the winding
+/// order to tell front faces from back faces.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Winding {
+    /// The corners of every triangle go around counterclockwise.
+    CounterClockwise,
+    /// The corners of every triangle go around clockwise.
+    Clockwise,
+}
+
+/// The `MeshSettings` struct holds the parameters that control how glyph quads are turned
+/// into vertex and index buffers.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MeshSettings<'a> {
+    /// The attributes of every vertex, in the order they are interleaved in the vertex buffer.
+    pub attributes: &'a [VertexAttribute],
+    /// The winding order of the triangles.
+    pub winding: Winding,
+    /// The convention of the vertex positions. With a top left origin the positions are the
+    /// screen coordinates of the layout, with the y-axis pointing down. With a bottom left
+    /// origin the y-axis points up, and the positions are measured from the bottom of a
+    /// target `height` pixels high.
+    pub origin: Origin,
+    /// The height of the target in pixels, used to flip the positions for a bottom left
+    /// origin. A height of zero negates the vertical screen coordinates.
+    pub height: f32,
+    /// The color of every vertex.
+    pub color: [f32; 4],
+}
+
+impl<'a> MeshSettings<'a> {
+    pub fn new() -> MeshSettings<'a> {
+        MeshSettings {
+            attributes: &DEFAULT_ATTRIBUTES,
+            winding: Winding::CounterClockwise,
+            origin: Origin::TopLeft,
+            height: 0.0,
+            color: [1.0, 1.0, 1.0, 1.0],
+        }
+    }
+
+    /// Return the number of floats in one vertex.
+    pub fn stride(&self) -> usize {
+        self.attributes.iter().map(|attribute| attribute.component_count()).sum()
+    }
+
+    /// Return the number of floats in a vertex in front of the first occurrence of an
+    /// attribute, or `None` when the vertices do not have the attribute.
+    pub fn offset(&self, attribute: VertexAttribute) -> Option<usize> {
+        let index = self.attributes.iter().position(|&other| other == attribute)?;
+
+        Some(self.attributes[..index].iter().map(|attribute| attribute.component_count()).sum())
+    }
+}
+
+impl<'a> Default for MeshSettings<'a> {
+    fn default() -> MeshSettings<'a> {
+        MeshSettings::new()
+    }
+}
+
+/// The vertex attributes of a mesh by default: a position, texture coordinates, and a color.
+pub const DEFAULT_ATTRIBUTES: [VertexAttribute; 3] = [
+    VertexAttribute::Position,
+    VertexAttribute::TexCoord,
+    VertexAttribute::Color,
+];
+
+/// A `Batch` is a range of the index buffer of a mesh drawing the glyphs of one atlas page.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Batch {
+    /// The atlas page the glyphs of the batch are stored on.
+    pub page: usize,
+    /// The position of the first index of the batch in the index buffer.
+    pub first_index: usize,
+    /// The number of indices in the batch.
+    pub index_count: usize,
+}
+
+/// A `Mesh` holds the interleaved vertices and the triangle indices of a run of glyph quads.
+/// Every quad has four vertices and two triangles, and the quads are grouped by atlas page,
+/// so each batch can be drawn with its page bound as the texture.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mesh {
+    /// The vertex attributes of every vertex, interleaved in the order of the mesh settings.
+    pub vertices: Vec<f32>,
+    /// The indices of the corners of the triangles into the vertices.
+    pub indices: Vec<u32>,
+    /// The index ranges of the atlas pages, in increasing page order.
+    pub batches: Vec<Batch>,
+}
+
+impl Mesh {
+    /// Return the indices as 16-bit integers, or `None` when some index does not fit into
+    /// 16 bits.
+    pub fn indices_u16(&self) -> Option<Vec<u16>> {
+        self.indices.iter().map(|&index| u16::try_from(index).ok()).collect()
+    }
+}
+
+/// Turn laid out glyphs of a bitmap font atlas into a mesh. The texture coordinates follow
+/// the origin of the atlas, matching its pages as they are uploaded.
+pub fn mesh_glyphs(atlas: &BitmapFontAtlas, glyphs: &[PositionedGlyph], settings: &MeshSettings) -> Mesh {
+    let mut sorted: Vec<&PositionedGlyph> = glyphs.iter().collect();
+    sorted.sort_by_key(|glyph| glyph.page);
+
+    let mut mesh = Mesh {
+        vertices: Vec::with_capacity(4 * settings.stride() * sorted.len()),
+        indices: Vec::with_capacity(6 * sorted.len()),
+        batches: vec![],
+    };
+    let triangles: [u32; 6] = match settings.winding {
+        Winding::CounterClockwise => [0, 1, 2, 0, 2, 3],
+        Winding::Clockwise => [0, 2, 1, 0, 3, 2],
+    };
+    for (quad, glyph) in sorted.iter().enumerate() {
+        match mesh.batches.last_mut() {
+            Some(batch) if batch.page == glyph.page => batch.index_count += 6,
+            _ => mesh.batches.push(Batch { page: glyph.page, first_index: 6 * quad, index_count: 6 }),
+        }
+
+        let rect = glyph.screen_rect;
+        let uv = glyph.uv_rect;
+        let y = |screen_y: f32| match settings.origin {
+            Origin::TopLeft => screen_y,
+            Origin::BottomLeft => settings.height - screen_y,
+        };
+        // The minimum corner of the texture coordinates is the top left corner of the glyph
+        // for a top left origin, and the bottom left corner for a bottom left origin.
+        let (v_top, v_bottom) = match atlas.origin {
+            Origin::TopLeft => (uv.y, uv.y + uv.height),
+            Origin::BottomLeft => (uv.y + uv.height, uv.y),
+        };
+        // The corners go around counterclockwise from the top left corner of the glyph.
+        let corners = [
+            ([rect.x, y(rect.y)], [uv.x, v_top]),
+            ([rect.x, y(rect.y + rect.height)], [uv.x, v_bottom]),
+            ([rect.x + rect.width, y(rect.y + rect.height)], [uv.x + uv.width, v_bottom]),
+            ([rect.x + rect.width, y(rect.y)], [uv.x + uv.width, v_top]),
+        ];
+
+        let first_vertex = 4 * quad as u32;
+        for (position, tex_coord) in corners.iter() {
+            for attribute in settings.attributes.iter() {
+                match attribute {
+                    VertexAttribute::Position => mesh.vertices.extend_from_slice(position),
+                    VertexAttribute::TexCoord => mesh.vertices.extend_from_slice(tex_coord),
+                    VertexAttribute::Color => mesh.vertices.extend_from_slice(&settings.color),
+                    VertexAttribute::Page => mesh.vertices.push(glyph.page as f32),
+                }
+            }
+        }
+        mesh.indices.extend(triangles.iter().map(|&corner| first_vertex + corner));
+    }
+
+    mesh
+}
+
+/// Lay out a string with a bitmap font atlas and turn the glyph quads into a mesh. See
+/// [`layout::layout`] and [`mesh_glyphs`].
+pub fn mesh(atlas: &BitmapFontAtlas, text: &str, layout_settings: &LayoutSettings, settings: &MeshSettings) -> Mesh {
+    let glyphs = layout::layout(atlas, text, layout_settings);
+
+    mesh_glyphs(atlas, &glyphs, settings)
+}
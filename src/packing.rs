@@ -0,0 +1,134 @@
+use crate::GlyphRect;
+
+
+/// A `SkylineNode` is one horizontal segment of the skyline. Everything above the
+/// segment's height is occupied.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+struct SkylineNode {
+    x: usize,
+    y: usize,
+    width: usize,
+}
+
+/// A `SkylinePacker` packs rectangles into a fixed size region using the skyline
+/// bottom left heuristic. The region is measured from its top left corner, so the skyline
+/// grows downwards from the top of the region.
+#[derive(Clone, Debug)]
+pub struct SkylinePacker {
+    /// The width of the packing region, in pixels.
+    width: usize,
+    /// The height of the packing region, in pixels.
+    height: usize,
+    /// The skyline segments, sorted from left to right and covering the whole width.
+    skyline: Vec<SkylineNode>,
+}
+
+impl SkylinePacker {
+    pub fn new(width: usize, height: usize) -> SkylinePacker {
+        SkylinePacker {
+            width: width,
+            height: height,
+            skyline: vec![SkylineNode { x: 0, y: 0, width: width }],
+        }
+    }
+
+    /// Return the width of the packing region in pixels.
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Return the height of the packing region in pixels.
+    #[inline]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Find the height at which a rectangle of the given width fits when its left edge sits
+    /// on the skyline segment `index`, if it fits at all.
+    fn fit(&self, index: usize, width: usize, height: usize) -> Option<usize> {
+        let x = self.skyline[index].x;
+        if x + width > self.width {
+            return None;
+        }
+
+        let mut y = 0;
+        let mut remaining = width as isize;
+        let mut i = index;
+        while remaining > 0 {
+            y = usize::max(y, self.skyline[i].y);
+            if y + height > self.height {
+                return None;
+            }
+            remaining -= self.skyline[i].width as isize;
+            i += 1;
+        }
+
+        Some(y)
+    }
+
+    /// Pack a rectangle with the given dimensions. This returns the position of the
+    /// rectangle inside the packing region, or `None` when the rectangle does not fit.
+    pub fn pack(&mut self, width: usize, height: usize) -> Option<GlyphRect> {
+        if width == 0 || height == 0 {
+            return Some(GlyphRect::new(0, 0, width, height));
+        }
+
+        let mut best: Option<(usize, usize, usize)> = None;
+        for index in 0..self.skyline.len() {
+            if let Some(y) = self.fit(index, width, height) {
+                let bottom = y + height;
+                let is_better = match best {
+                    Some((_, best_y, best_width)) => {
+                        bottom < best_y + height ||
+                            (bottom == best_y + height && self.skyline[index].width < best_width)
+                    }
+                    None => true,
+                };
+                if is_better {
+                    best = Some((index, y, self.skyline[index].width));
+                }
+            }
+        }
+
+        let (index, y, _) = best?;
+        let x = self.skyline[index].x;
+        self.insert(index, x, y + height, width);
+
+        Some(GlyphRect::new(x, y, width, height))
+    }
+
+    /// Raise the skyline to `y` over the span starting at `x` with the given width.
+    fn insert(&mut self, index: usize, x: usize, y: usize, width: usize) {
+        self.skyline.insert(index, SkylineNode { x: x, y: y, width: width });
+
+        // Shrink or remove the segments covered by the new segment.
+        let right = x + width;
+        let i = index + 1;
+        while i < self.skyline.len() {
+            let node = self.skyline[i];
+            if node.x >= right {
+                break;
+            }
+            let node_right = node.x + node.width;
+            if node_right <= right {
+                self.skyline.remove(i);
+            } else {
+                self.skyline[i].x = right;
+                self.skyline[i].width = node_right - right;
+                break;
+            }
+        }
+
+        // Merge neighboring segments at the same height.
+        let mut i = 0;
+        while i + 1 < self.skyline.len() {
+            if self.skyline[i].y == self.skyline[i + 1].y {
+                self.skyline[i].width += self.skyline[i + 1].width;
+                self.skyline.remove(i + 1);
+            } else {
+                i += 1;
+            }
+        }
+    }
+}
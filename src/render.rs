@@ -0,0 +1,132 @@
+use crate::layout::{self, LayoutSettings};
+use crate::{BitmapFontAtlas, BitmapFontAtlasImage, Error, ErrorKind, GlyphRect, Origin};
+use image::RgbaImage;
+
+
+/// Return the coverage of the pixel at column `x` and row `y` of an atlas page in the
+/// interval [0,1], counting rows from the top of the page as it is stored in the PNG file.
+/// The coverage is the alpha channel for formats with alpha, and the first channel otherwise.
+fn coverage(image: &BitmapFontAtlasImage, x: usize, y: usize) -> f32 {
+    let memory_y = match image.origin() {
+        Origin::TopLeft => y,
+        Origin::BottomLeft => image.height() - y - 1,
+    };
+    let format = image.format();
+    let pixel = image.pixel(x, memory_y);
+    let channel = match format.channel_count() {
+        2 | 4 => &pixel[(pixel.len() - format.bytes_per_channel())..],
+        _ => &pixel[..format.bytes_per_channel()],
+    };
+
+    match *channel {
+        [value] => value as f32 / 255.0,
+        [first, second] => u16::from_ne_bytes([first, second]) as f32 / 65535.0,
+        _ => unreachable!("pixel channels are one or two bytes wide"),
+    }
+}
+
+/// Sample the coverage of a glyph at a point of its pixel rectangle, given in pixels from
+/// the top left corner of the rectangle, interpolating between the nearest pixel centers.
+/// Points near the edges of the rectangle only sample pixels inside the rectangle.
+fn sample(image: &BitmapFontAtlasImage, region: GlyphRect, x: f32, y: f32) -> f32 {
+    let clamp = |value: f32, len: usize| f32::min(f32::max(value - 0.5, 0.0), (len - 1) as f32);
+    let (x, y) = (clamp(x, region.width), clamp(y, region.height));
+    let (x0, y0) = (x.floor() as usize, y.floor() as usize);
+    let (x1, y1) = (usize::min(x0 + 1, region.width - 1), usize::min(y0 + 1, region.height - 1));
+    let (tx, ty) = (x - x0 as f32, y - y0 as f32);
+    let at = |x: usize, y: usize| coverage(image, region.x + x, region.y + y);
+    let top = at(x0, y0) * (1.0 - tx) + at(x1, y0) * tx;
+    let bottom = at(x0, y1) * (1.0 - tx) + at(x1, y1) * tx;
+
+    top * (1.0 - ty) + bottom * ty
+}
+
+/// Blend a color over a pixel of a buffer with straight alpha.
+fn blend(pixel: &mut [u8], color: [u8; 4], coverage: f32) {
+    let alpha = color[3] as f32 / 255.0 * coverage;
+    if alpha <= 0.0 {
+        return;
+    }
+    let pixel_alpha = pixel[3] as f32 / 255.0;
+    let out_alpha = alpha + pixel_alpha * (1.0 - alpha);
+    for channel in 0..3 {
+        let blended = (color[channel] as f32 * alpha + pixel[channel] as f32 * pixel_alpha * (1.0 - alpha)) / out_alpha;
+        pixel[channel] = f32::round(blended) as u8;
+    }
+    pixel[3] = f32::round(out_alpha * 255.0) as u8;
+}
+
+/// Draw a string into an RGBA buffer with a bitmap font atlas. The buffer holds `width`
+/// by `height` pixels of four 8-bit channels with straight alpha, stored row by row from
+/// the top of the image down.
+///
+/// The text is laid out with [`layout::layout`], so the settings place the baseline of the
+/// first line in the buffer, and the glyphs are blended over the buffer in the given
+/// color, scaled by their coverage in the atlas. Glyphs stored as signed distance fields
+/// are drawn with an antialiased edge one pixel wide at any size.
+pub fn render_to_buffer(
+    atlas: &BitmapFontAtlas, text: &str, settings: &LayoutSettings, color: [u8; 4],
+    buffer: &mut [u8], width: usize, height: usize) -> Result<(), Error> {
+
+    if width.checked_mul(height).and_then(|pixels| pixels.checked_mul(4)) != Some(buffer.len()) {
+        return Err(Error::new(
+            ErrorKind::InvalidRenderTarget,
+            format!("a {}x{} RGBA buffer does not fit in {} bytes", width, height, buffer.len()).into()
+        ));
+    }
+
+    for glyph in layout::layout(atlas, text, settings) {
+        let region = match atlas.glyph_rect(glyph.code_point) {
+            Some(region) if region.width > 0 && region.height > 0 => region,
+            _ => continue,
+        };
+        let image = match atlas.pages.get(glyph.page) {
+            Some(image) => image,
+            None => continue,
+        };
+        let fits_x = region.x.checked_add(region.width).is_some_and(|right| right <= image.width());
+        let fits_y = region.y.checked_add(region.height).is_some_and(|bottom| bottom <= image.height());
+        if !fits_x || !fits_y {
+            continue;
+        }
+
+        let rect = glyph.screen_rect;
+        let scale_x = region.width as f32 / rect.width;
+        let scale_y = region.height as f32 / rect.height;
+        let left = f32::max(rect.x.floor(), 0.0) as usize;
+        let top = f32::max(rect.y.floor(), 0.0) as usize;
+        let right = usize::min(f32::max((rect.x + rect.width).ceil(), 0.0) as usize, width);
+        let bottom = usize::min(f32::max((rect.y + rect.height).ceil(), 0.0) as usize, height);
+        for y in top..bottom {
+            for x in left..right {
+                // Sample the glyph at the center of the destination pixel.
+                let center_x = x as f32 + 0.5 - rect.x;
+                let center_y = y as f32 + 0.5 - rect.y;
+                if center_x < 0.0 || center_y < 0.0 || center_x >= rect.width || center_y >= rect.height {
+                    continue;
+                }
+                let value = sample(image, region, center_x * scale_x, center_y * scale_y);
+                let coverage = match atlas.distance_field {
+                    // The distance field stores distances of up to `range` atlas pixels on
+                    // either side of the outline.
+                    Some(distance_field) => {
+                        let distance = (value - 0.5) * 2.0 * distance_field.range / scale_x;
+                        (distance + 0.5).clamp(0.0, 1.0)
+                    }
+                    None => value,
+                };
+                let offset = (y * width + x) * 4;
+                blend(&mut buffer[offset..(offset + 4)], color, coverage);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Draw a string into an RGBA image with a bitmap font atlas. See [`render_to_buffer`].
+pub fn render(atlas: &BitmapFontAtlas, text: &str, settings: &LayoutSettings, color: [u8; 4], target: &mut RgbaImage) {
+    let (width, height) = (target.width() as usize, target.height() as usize);
+    render_to_buffer(atlas, text, settings, color, target, width, height)
+        .expect("an RGBA image always matches its dimensions");
+}
@@ -0,0 +1,239 @@
+use crate::{AtlasLayout, BitmapFontAtlas, BitmapFontAtlasMetadata};
+use std::collections::HashMap;
+use std::fmt;
+
+
+/// A `Diagnostic` describes one way in which a bitmap font atlas violates the invariants
+/// of the bmfa format.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Diagnostic {
+    /// The dimensions of a grid atlas do not match its rows and columns of glyph slots.
+    GridDimensionMismatch {
+        width: usize,
+        height: usize,
+        columns: usize,
+        rows: usize,
+        slot_glyph_size: usize,
+    },
+    /// The slot glyph size is not the sum of the padding and the glyph size.
+    SlotSizeMismatch {
+        slot_glyph_size: usize,
+        padding: usize,
+        glyph_size: usize,
+    },
+    /// The font size the atlas was rasterized at is not a positive number.
+    InvalidFontSize {
+        font_size: f32,
+    },
+    /// The atlas declares no pages.
+    NoPages,
+    /// An atlas page does not match the dimensions of the atlas, or its buffer does not
+    /// match its dimensions and pixel format.
+    ImageDimensionMismatch {
+        page: usize,
+        width: usize,
+        height: usize,
+        len_bytes: usize,
+    },
+    /// A glyph is stored on a page the atlas does not have.
+    GlyphPageOutOfRange {
+        code_point: usize,
+        page: usize,
+    },
+    /// A glyph in a grid atlas lies outside of the grid of glyph slots.
+    GlyphOutsideGrid {
+        code_point: usize,
+        row: usize,
+        column: usize,
+    },
+    /// A glyph in a packed atlas has no pixel rectangle, or its rectangle does not lie
+    /// inside the page.
+    GlyphOutsidePage {
+        code_point: usize,
+    },
+    /// Two glyphs in a grid atlas occupy the same glyph slot.
+    DuplicateSlot {
+        code_point: usize,
+        other_code_point: usize,
+        page: usize,
+        row: usize,
+        column: usize,
+    },
+    /// The key of a glyph in the glyph table does not match the code point of the glyph.
+    CodePointMismatch {
+        key: usize,
+        code_point: usize,
+    },
+    /// The `.notdef` glyph designated by the atlas is missing from the glyph table.
+    MissingNotdef {
+        code_point: usize,
+    },
+    /// A normalized glyph parameter lies outside of the interval [0,1].
+    ValueOutOfRange {
+        code_point: usize,
+        field: &'static str,
+        value: f32,
+    },
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Diagnostic::GridDimensionMismatch { width, height, columns, rows, slot_glyph_size } => write!(
+                f, "the atlas is {}x{} pixels but its grid of {}x{} slots of {} pixels is {}x{} pixels",
+                width, height, columns, rows, slot_glyph_size, columns.saturating_mul(slot_glyph_size), rows.saturating_mul(slot_glyph_size)
+            ),
+            Diagnostic::SlotSizeMismatch { slot_glyph_size, padding, glyph_size } => write!(
+                f, "the slot glyph size {} is not the sum of the padding {} and the glyph size {}",
+                slot_glyph_size, padding, glyph_size
+            ),
+            Diagnostic::InvalidFontSize { font_size } => write!(
+                f, "the font size {} is not a positive number", font_size
+            ),
+            Diagnostic::NoPages => write!(f, "the atlas has no pages"),
+            Diagnostic::ImageDimensionMismatch { page, width, height, len_bytes } => write!(
+                f, "page {} is {}x{} pixels stored in {} bytes, which does not match the atlas",
+                page, width, height, len_bytes
+            ),
+            Diagnostic::GlyphPageOutOfRange { code_point, page } => write!(
+                f, "glyph {} is stored on page {}, which the atlas does not have", code_point, page
+            ),
+            Diagnostic::GlyphOutsideGrid { code_point, row, column } => write!(
+                f, "glyph {} is stored in row {} and column {}, outside of the grid", code_point, row, column
+            ),
+            Diagnostic::GlyphOutsidePage { code_point } => write!(
+                f, "glyph {} does not lie inside its page", code_point
+            ),
+            Diagnostic::DuplicateSlot { code_point, other_code_point, page, row, column } => write!(
+                f, "glyphs {} and {} both occupy row {} and column {} of page {}",
+                other_code_point, code_point, row, column, page
+            ),
+            Diagnostic::CodePointMismatch { key, code_point } => write!(
+                f, "the glyph stored under code point {} has code point {}", key, code_point
+            ),
+            Diagnostic::MissingNotdef { code_point } => write!(
+                f, "the .notdef glyph {} is missing from the glyph table", code_point
+            ),
+            Diagnostic::ValueOutOfRange { code_point, field, value } => write!(
+                f, "the {} of glyph {} is {}, outside of the interval [0,1]", field, code_point, value
+            ),
+        }
+    }
+}
+
+impl BitmapFontAtlasMetadata {
+    /// Check the metadata against the invariants of the bmfa format, returning every
+    /// violation found. The metadata is valid when the list is empty.
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = vec![];
+        // The metadata may come from an untrusted file, so none of the arithmetic on it
+        // may overflow.
+        if self.padding.checked_add(self.glyph_size) != Some(self.slot_glyph_size) {
+            diagnostics.push(Diagnostic::SlotSizeMismatch {
+                slot_glyph_size: self.slot_glyph_size,
+                padding: self.padding,
+                glyph_size: self.glyph_size,
+            });
+        }
+        if !(self.font_size.is_finite() && self.font_size > 0.0) {
+            diagnostics.push(Diagnostic::InvalidFontSize { font_size: self.font_size });
+        }
+        let is_grid = self.layout == AtlasLayout::Grid;
+        let grid_width = self.columns.checked_mul(self.slot_glyph_size);
+        let grid_height = self.rows.checked_mul(self.slot_glyph_size);
+        if is_grid && (grid_width != Some(self.width) || grid_height != Some(self.height)) {
+            diagnostics.push(Diagnostic::GridDimensionMismatch {
+                width: self.width,
+                height: self.height,
+                columns: self.columns,
+                rows: self.rows,
+                slot_glyph_size: self.slot_glyph_size,
+            });
+        }
+        if self.page_count == 0 {
+            diagnostics.push(Diagnostic::NoPages);
+        }
+        if let Some(notdef) = self.notdef.filter(|notdef| !self.glyph_metadata.contains_key(notdef)) {
+            diagnostics.push(Diagnostic::MissingNotdef { code_point: notdef });
+        }
+
+        // Visit the glyphs in code point order so the report does not depend on the
+        // iteration order of the glyph table.
+        let mut keys: Vec<&usize> = self.glyph_metadata.keys().collect();
+        keys.sort();
+        let mut slots = HashMap::new();
+        for &key in keys {
+            let glyph = &self.glyph_metadata[&key];
+            let code_point = glyph.code_point;
+            if key != code_point {
+                diagnostics.push(Diagnostic::CodePointMismatch { key: key, code_point: code_point });
+            }
+            if glyph.page >= self.page_count {
+                diagnostics.push(Diagnostic::GlyphPageOutOfRange { code_point: code_point, page: glyph.page });
+            }
+
+            let mut normalized = vec![("x_min", glyph.x_min), ("y_min", glyph.y_min)];
+            if is_grid {
+                normalized.push(("width", glyph.width));
+                normalized.push(("height", glyph.height));
+            }
+            for (field, value) in normalized {
+                if !(0.0..=1.0).contains(&value) {
+                    diagnostics.push(Diagnostic::ValueOutOfRange { code_point: code_point, field: field, value: value });
+                }
+            }
+
+            match (self.layout, glyph.rect) {
+                (AtlasLayout::Grid, _) => {
+                    if glyph.row >= self.rows || glyph.column >= self.columns {
+                        diagnostics.push(Diagnostic::GlyphOutsideGrid {
+                            code_point: code_point,
+                            row: glyph.row,
+                            column: glyph.column,
+                        });
+                    } else if let Some(other_code_point) = slots.insert((glyph.page, glyph.row, glyph.column), code_point) {
+                        diagnostics.push(Diagnostic::DuplicateSlot {
+                            code_point: code_point,
+                            other_code_point: other_code_point,
+                            page: glyph.page,
+                            row: glyph.row,
+                            column: glyph.column,
+                        });
+                    }
+                }
+                (AtlasLayout::Packed, Some(rect))
+                    if rect.x.checked_add(rect.width).is_some_and(|right| right <= self.width)
+                        && rect.y.checked_add(rect.height).is_some_and(|bottom| bottom <= self.height) => {}
+                (AtlasLayout::Packed, _) => {
+                    diagnostics.push(Diagnostic::GlyphOutsidePage { code_point: code_point });
+                }
+            }
+        }
+
+        diagnostics
+    }
+}
+
+impl BitmapFontAtlas {
+    /// Check the atlas against the invariants of the bmfa format, returning every violation
+    /// found. Besides the checks on the metadata, every page must have the dimensions of
+    /// the atlas, with a buffer matching its dimensions and pixel format. The atlas is
+    /// valid when the list is empty.
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = self.metadata().validate();
+        for (page, image) in self.pages.iter().enumerate() {
+            // The dimensions of a page built by hand may overflow.
+            let expected_len_bytes = image.checked_len_bytes();
+            if image.width() != self.width || image.height() != self.height || Some(image.len_bytes()) != expected_len_bytes {
+                diagnostics.push(Diagnostic::ImageDimensionMismatch {
+                    page: page,
+                    width: image.width(),
+                    height: image.height(),
+                    len_bytes: image.len_bytes(),
+                });
+            }
+        }
+
+        diagnostics
+    }
+}
@@ -67,7 +67,7 @@ fn bmfa_loader_should_load_valid_bmfa_file() {
 #[test]
 fn bmfa_file_dimensions_should_match_buffer_length() {
     let font_atlas = bmfa::load(SAMPLE_FILE).unwrap();
-    let expected = font_atlas.image.len_bytes();
+    let expected = font_atlas.pages[0].len_bytes();
     let result = 4 * font_atlas.width * font_atlas.height;
 
     assert_eq!(result, expected);
@@ -296,5 +296,686 @@ fn bmfa_file_written_and_then_read_should_match_glyph_metadata() {
 fn bmfa_file_written_and_then_read_should_match_atlases() {
     let test = read_write_test(SAMPLE_FILE);
 
-    assert_eq!(test.result_atlas.image, test.expected_atlas.image);
+    assert_eq!(test.result_atlas.pages, test.expected_atlas.pages);
+}
+
+/// A bmfa file written before distance field atlases existed stores glyph coverage, so it
+/// should load without any distance field parameters.
+#[test]
+fn bmfa_file_without_distance_field_should_load_as_coverage_atlas() {
+    let font_atlas = bmfa::load(SAMPLE_FILE).unwrap();
+
+    assert_eq!(font_atlas.distance_field, None);
+}
+
+/// A bmfa file written before packed atlases existed should load as a grid atlas, with
+/// every glyph located by its row and column.
+#[test]
+fn bmfa_file_without_layout_should_load_as_grid_atlas() {
+    let font_atlas = bmfa::load(SAMPLE_FILE).unwrap();
+
+    assert_eq!(font_atlas.layout, bmfa::AtlasLayout::Grid);
+    assert!(font_atlas.glyph_metadata.values().all(|glyph| glyph.rect.is_none()));
+}
+
+/// A bmfa file written before multiple page atlases existed stores exactly one atlas page,
+/// and every glyph lives on that page.
+#[test]
+fn bmfa_file_without_page_count_should_load_a_single_page() {
+    let font_atlas = bmfa::load(SAMPLE_FILE).unwrap();
+
+    assert_eq!(font_atlas.pages.len(), 1);
+    assert_eq!(font_atlas.metadata().page_count, 1);
+    assert!(font_atlas.glyph_metadata.values().all(|glyph| glyph.page == 0));
+}
+
+/// Writing a single page atlas should keep the legacy archive layout, so older readers
+/// can still load it.
+#[test]
+fn bmfa_file_with_a_single_page_should_be_written_with_one_image_file() {
+    let font_atlas = bmfa::load(SAMPLE_FILE).unwrap();
+    let mut cursor = io::Cursor::new(vec![]);
+    bmfa::to_writer(&mut cursor, &font_atlas).unwrap();
+    let mut zip_file = zip::ZipArchive::new(cursor).unwrap();
+
+    assert_eq!(zip_file.len(), 2);
+    assert!(zip_file.by_name("atlas.png").is_ok());
+}
+
+/// A bmfa file written before kerning tables existed should load with an empty kerning
+/// table, so every pair of glyphs needs no adjustment.
+#[test]
+fn bmfa_file_without_kerning_should_load_with_empty_kerning_table() {
+    let font_atlas = bmfa::load(SAMPLE_FILE).unwrap();
+
+    assert!(font_atlas.kerning.is_empty());
+    assert_eq!(font_atlas.kern('A' as usize, 'V' as usize), 0.0);
+}
+
+/// A bmfa file written before glyph advances existed should load with each glyph advancing
+/// by its width.
+#[test]
+fn bmfa_file_without_advances_should_default_advances_to_widths() {
+    let font_atlas = bmfa::load(SAMPLE_FILE).unwrap();
+
+    for glyph in font_atlas.glyph_metadata.values() {
+        assert_eq!(glyph.advance, glyph.width);
+    }
+}
+
+/// A bmfa file written before line metrics existed should load with line metrics derived
+/// from the extent of its glyphs around the baseline.
+#[test]
+fn bmfa_file_without_line_metrics_should_derive_line_metrics_from_glyphs() {
+    let font_atlas = bmfa::load(SAMPLE_FILE).unwrap();
+    let glyph = font_atlas.glyph_metadata[&('g' as usize)];
+
+    assert_eq!(font_atlas.font_size, font_atlas.glyph_size as f32);
+    assert!(font_atlas.ascent > 0.0);
+    assert!(font_atlas.descent <= glyph.y_offset);
+    assert_eq!(font_atlas.line_gap, 0.0);
+    assert!(font_atlas.line_height() > font_atlas.ascent);
+}
+
+/// The sample atlas stores four 8-bit channels per pixel.
+#[test]
+fn bmfa_file_should_load_with_rgba8_pixel_format() {
+    let font_atlas = bmfa::load(SAMPLE_FILE).unwrap();
+    let page = &font_atlas.pages[0];
+
+    assert_eq!(page.format(), bmfa::PixelFormat::Rgba8);
+    assert_eq!(page.width_in_bytes(), 4 * page.width());
+}
+
+/// The pixel accessor should return the bytes of a pixel, honoring the pixel format.
+#[test]
+fn atlas_image_pixel_should_index_by_pixel_format() {
+    let data: Vec<u8> = (0..12).collect();
+    let image = bmfa::BitmapFontAtlasImage::new(data, 3, 2, bmfa::PixelFormat::Rg8, bmfa::Origin::TopLeft);
+
+    assert_eq!(image.pixel(0, 0), &[0, 1]);
+    assert_eq!(image.pixel(2, 0), &[4, 5]);
+    assert_eq!(image.pixel(1, 1), &[8, 9]);
+}
+
+/// A writer that fails once anything is written past a fixed number of bytes.
+struct LimitedWriter {
+    cursor: io::Cursor<Vec<u8>>,
+    limit: u64,
+}
+
+impl LimitedWriter {
+    fn new(limit: u64) -> LimitedWriter {
+        LimitedWriter {
+            cursor: io::Cursor::new(vec![]),
+            limit: limit,
+        }
+    }
+}
+
+impl io::Write for LimitedWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.cursor.position() + buf.len() as u64 > self.limit {
+            return Err(io::Error::new(io::ErrorKind::WriteZero, "the writer is full"));
+        }
+        self.cursor.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.cursor.flush()
+    }
+}
+
+impl io::Seek for LimitedWriter {
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        self.cursor.seek(pos)
+    }
+}
+
+/// Return the size of the sample atlas when written out.
+fn written_sample_size() -> u64 {
+    let font_atlas = bmfa::load(SAMPLE_FILE).unwrap();
+    let mut cursor = io::Cursor::new(vec![]);
+    bmfa::to_writer(&mut cursor, &font_atlas).unwrap();
+
+    cursor.into_inner().len() as u64
+}
+
+/// Writing to a directory that does not exist should fail to create the file.
+#[test]
+fn bmfa_file_written_to_missing_directory_should_fail_to_create_file() {
+    let font_atlas = bmfa::load(SAMPLE_FILE).unwrap();
+    let path = std::env::temp_dir().join("bmfa_missing_directory").join("missing").join("freemono.bmfa");
+    let result = bmfa::write_to_file(path, &font_atlas);
+
+    assert_eq!(result.err().map(|e| e.kind()), Some(bmfa::ErrorKind::CannotCreateFile));
+}
+
+/// A writer failure while writing the metadata should be reported as such.
+#[test]
+fn bmfa_file_written_to_full_writer_should_fail_to_write_metadata() {
+    let font_atlas = bmfa::load(SAMPLE_FILE).unwrap();
+    let result = bmfa::to_writer(LimitedWriter::new(16), &font_atlas);
+
+    assert_eq!(result.err().map(|e| e.kind()), Some(bmfa::ErrorKind::CannotWriteMetadata));
+}
+
+/// A writer failure while writing the atlas image should be reported as an encoding failure.
+#[test]
+fn bmfa_file_written_to_full_writer_should_fail_to_encode_atlas_image() {
+    let font_atlas = bmfa::load(SAMPLE_FILE).unwrap();
+    let result = bmfa::to_writer(LimitedWriter::new(written_sample_size() - 4096), &font_atlas);
+
+    assert_eq!(result.err().map(|e| e.kind()), Some(bmfa::ErrorKind::CannotEncodeAtlasImage));
+}
+
+/// A writer failure while writing the archive directory should be reported as a
+/// finalization failure.
+#[test]
+fn bmfa_file_written_to_full_writer_should_fail_to_finalize_archive() {
+    let font_atlas = bmfa::load(SAMPLE_FILE).unwrap();
+    let result = bmfa::to_writer(LimitedWriter::new(written_sample_size() - 1), &font_atlas);
+
+    assert_eq!(result.err().map(|e| e.kind()), Some(bmfa::ErrorKind::CannotFinalizeArchive));
+}
+
+/// Writing an atlas whose page does not match the atlas dimensions should fail before
+/// anything is written.
+#[test]
+fn bmfa_file_with_mismatched_page_should_fail_to_write() {
+    let mut font_atlas = bmfa::load(SAMPLE_FILE).unwrap();
+    font_atlas.width /= 2;
+    let mut cursor = io::Cursor::new(vec![]);
+    let result = bmfa::to_writer(&mut cursor, &font_atlas);
+
+    assert_eq!(result.err().map(|e| e.kind()), Some(bmfa::ErrorKind::ImageDimensionMismatch));
+    assert!(cursor.into_inner().is_empty());
+}
+
+/// Writing an atlas whose page dimensions overflow the length of its buffer should fail
+/// instead of panicking.
+#[test]
+fn bmfa_file_with_overflowing_page_dimensions_should_fail_to_write() {
+    let mut font_atlas = small_atlas();
+    font_atlas.width = usize::MAX / 2;
+    font_atlas.pages[0] = bmfa::BitmapFontAtlasImage::new(
+        vec![], font_atlas.width, font_atlas.height, bmfa::PixelFormat::Rgba8, font_atlas.origin
+    );
+    let mut cursor = io::Cursor::new(vec![]);
+    let result = bmfa::to_writer(&mut cursor, &font_atlas);
+
+    assert_eq!(result.err().map(|e| e.kind()), Some(bmfa::ErrorKind::ImageDimensionMismatch));
+    assert!(cursor.into_inner().is_empty());
+}
+
+/// Writing an atlas without pages should fail before anything is written, and its image
+/// data should be empty instead of panicking.
+#[test]
+fn bmfa_file_without_pages_should_fail_to_write() {
+    let mut font_atlas = bmfa::load(SAMPLE_FILE).unwrap();
+    font_atlas.pages.clear();
+    let mut cursor = io::Cursor::new(vec![]);
+    let result = bmfa::to_writer(&mut cursor, &font_atlas);
+
+    assert_eq!(result.err().map(|e| e.kind()), Some(bmfa::ErrorKind::InvalidAtlasParameters));
+    assert!(cursor.into_inner().is_empty());
+    assert!(AsRef::<[u8]>::as_ref(&font_atlas).is_empty());
+}
+
+/// The underlying cause of an error should be available through its source.
+#[test]
+fn bmfa_write_error_should_expose_its_source() {
+    use std::error::Error;
+
+    let font_atlas = bmfa::load(SAMPLE_FILE).unwrap();
+    let path = std::env::temp_dir().join("bmfa_missing_directory").join("missing").join("freemono.bmfa");
+    let error = bmfa::write_to_file(path, &font_atlas).unwrap_err();
+    let source = error.source().and_then(|source| source.downcast_ref::<io::Error>());
+
+    assert_eq!(source.map(|source| source.kind()), Some(io::ErrorKind::NotFound));
+}
+
+/// Read the metadata of an archive as raw JSON.
+fn read_metadata_json(archive: &[u8]) -> serde_json::Value {
+    let mut zip_file = zip::ZipArchive::new(io::Cursor::new(archive)).unwrap();
+    let metadata_file = zip_file.by_name("metadata.json").unwrap();
+
+    serde_json::from_reader(metadata_file).unwrap()
+}
+
+/// Copy an archive, replacing its metadata with the given JSON.
+fn replace_metadata_json(archive: &[u8], metadata: &serde_json::Value) -> Vec<u8> {
+    use std::io::Write;
+
+    let mut zip_file = zip::ZipArchive::new(io::Cursor::new(archive)).unwrap();
+    let mut writer = zip::ZipWriter::new(io::Cursor::new(vec![]));
+    let options = zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+    for i in 0..zip_file.len() {
+        let mut file = zip_file.by_index(i).unwrap();
+        writer.start_file(file.name(), options).unwrap();
+        if file.name() == "metadata.json" {
+            serde_json::to_writer(&mut writer, metadata).unwrap();
+        } else {
+            let mut contents = vec![];
+            io::Read::read_to_end(&mut file, &mut contents).unwrap();
+            writer.write_all(&contents).unwrap();
+        }
+    }
+
+    writer.finish().unwrap().into_inner()
+}
+
+/// Copy an archive, compressing every file with the given method.
+fn recompress(archive: &[u8], method: zip::CompressionMethod) -> Vec<u8> {
+    let mut zip_file = zip::ZipArchive::new(io::Cursor::new(archive)).unwrap();
+    let mut writer = zip::ZipWriter::new(io::Cursor::new(vec![]));
+    let options = zip::write::FileOptions::default().compression_method(method);
+    for i in 0..zip_file.len() {
+        let mut file = zip_file.by_index(i).unwrap();
+        writer.start_file(file.name(), options).unwrap();
+        io::copy(&mut file, &mut writer).unwrap();
+    }
+
+    writer.finish().unwrap().into_inner()
+}
+
+/// Archives written by other zip tools may compress their files with bzip2 or zstd, and
+/// should load the same as deflated ones.
+#[test]
+fn bmfa_file_compressed_with_bzip2_or_zstd_should_load() {
+    let archive = written_sample();
+    let expected_atlas = bmfa::from_reader(io::Cursor::new(&archive)).unwrap();
+    for &method in [zip::CompressionMethod::Bzip2, zip::CompressionMethod::Zstd].iter() {
+        let result_atlas = bmfa::from_reader(io::Cursor::new(recompress(&archive, method))).unwrap();
+
+        assert_eq!(result_atlas.metadata(), expected_atlas.metadata());
+        assert_eq!(result_atlas.pages, expected_atlas.pages);
+    }
+}
+
+/// Write the sample atlas out into memory.
+fn written_sample() -> Vec<u8> {
+    let font_atlas = bmfa::load(SAMPLE_FILE).unwrap();
+    let mut cursor = io::Cursor::new(vec![]);
+    bmfa::to_writer(&mut cursor, &font_atlas).unwrap();
+
+    cursor.into_inner()
+}
+
+/// A written atlas should record the current format version in its metadata.
+#[test]
+fn bmfa_file_should_be_written_with_the_current_format_version() {
+    let metadata = read_metadata_json(&written_sample());
+
+    assert_eq!(metadata["version"].as_u64(), Some(bmfa::FORMAT_VERSION));
+}
+
+/// Metadata without a version number should be read as the first format version and
+/// upgraded to the current structures.
+#[test]
+fn bmfa_file_without_version_should_be_migrated() {
+    let archive = written_sample();
+    let mut metadata = read_metadata_json(&archive);
+    let metadata_object = metadata.as_object_mut().unwrap();
+    for key in ["version", "ascent", "descent", "line_gap", "font_size"].iter() {
+        metadata_object.remove(*key);
+    }
+    let expected_atlas = bmfa::from_reader(io::Cursor::new(&archive)).unwrap();
+    let result_atlas = bmfa::from_reader(io::Cursor::new(replace_metadata_json(&archive, &metadata))).unwrap();
+
+    assert_eq!(result_atlas.metadata(), expected_atlas.metadata());
+}
+
+/// Metadata from a newer format version should be rejected with a dedicated error.
+#[test]
+fn bmfa_file_with_newer_version_should_fail_to_load() {
+    let archive = written_sample();
+    let mut metadata = read_metadata_json(&archive);
+    metadata["version"] = serde_json::Value::from(bmfa::FORMAT_VERSION + 1);
+    let result = bmfa::from_reader(io::Cursor::new(replace_metadata_json(&archive, &metadata)));
+
+    assert_eq!(result.err().map(|e| e.kind()), Some(bmfa::ErrorKind::UnsupportedFormatVersion));
+}
+
+/// A version number that is not a positive integer should be rejected as corrupt metadata.
+#[test]
+fn bmfa_file_with_invalid_version_should_fail_to_load() {
+    let archive = written_sample();
+    let mut metadata = read_metadata_json(&archive);
+    metadata["version"] = serde_json::Value::from("two");
+    let result = bmfa::from_reader(io::Cursor::new(replace_metadata_json(&archive, &metadata)));
+
+    assert_eq!(result.err().map(|e| e.kind()), Some(bmfa::ErrorKind::CannotLoadAtlasMetadata));
+}
+
+/// Write the sample atlas out into memory with the given options.
+fn written_sample_with_options(options: &bmfa::WriteOptions) -> Vec<u8> {
+    let font_atlas = bmfa::load(SAMPLE_FILE).unwrap();
+    let mut cursor = io::Cursor::new(vec![]);
+    bmfa::to_writer_with_options(&mut cursor, &font_atlas, options).unwrap();
+
+    cursor.into_inner()
+}
+
+/// Compressing the archive entries should make the archive smaller.
+#[test]
+fn bmfa_file_written_with_deflate_should_be_smaller() {
+    let options = bmfa::WriteOptions {
+        archive_compression: bmfa::ArchiveCompression::Deflated,
+        compression_level: Some(9),
+        ..bmfa::WriteOptions::default()
+    };
+    let stored = written_sample_with_options(&bmfa::WriteOptions::default());
+    let deflated = written_sample_with_options(&options);
+
+    assert!(deflated.len() < stored.len());
+}
+
+/// Compact metadata should be written on a single line.
+#[test]
+fn bmfa_file_written_with_compact_json_should_have_single_line_metadata() {
+    let options = bmfa::WriteOptions {
+        pretty_json: false,
+        ..bmfa::WriteOptions::default()
+    };
+    let archive = written_sample_with_options(&options);
+    let mut zip_file = zip::ZipArchive::new(io::Cursor::new(archive)).unwrap();
+    let mut metadata = String::new();
+    io::Read::read_to_string(&mut zip_file.by_name("metadata.json").unwrap(), &mut metadata).unwrap();
+
+    assert!(!metadata.contains('\n'));
+}
+
+/// Build a small atlas whose glyphs have differing pixels, so that every PNG filter has
+/// something to work on.
+fn small_atlas() -> bmfa::BitmapFontAtlas {
+    let gradient = |width: usize, height: usize| {
+        let data = (0..(width * height * 4)).map(|i| (i * 7) as u8).collect();
+        bmfa::GlyphBitmap::new(data, width, height)
+    };
+
+    bmfa::BitmapFontAtlasBuilder::new(16, 2)
+        .glyph('A' as usize, gradient(12, 14))
+        .glyph('g' as usize, gradient(9, 13))
+        .glyph('.' as usize, gradient(3, 3))
+        .build()
+        .unwrap()
+}
+
+/// An atlas written with any combination of options should read back unchanged.
+#[test]
+fn bmfa_file_written_with_options_and_then_read_should_match() {
+    let expected_atlas = small_atlas();
+    let archive_options = [
+        (bmfa::ArchiveCompression::Stored, None),
+        (bmfa::ArchiveCompression::Deflated, None),
+        (bmfa::ArchiveCompression::Deflated, Some(1)),
+    ];
+    let png_options = [
+        (bmfa::PngCompression::Default, bmfa::PngFilter::NoFilter),
+        (bmfa::PngCompression::Fast, bmfa::PngFilter::Up),
+        (bmfa::PngCompression::Best, bmfa::PngFilter::Paeth),
+        (bmfa::PngCompression::Best, bmfa::PngFilter::Avg),
+    ];
+    for &(archive_compression, compression_level) in archive_options.iter() {
+        for &(png_compression, png_filter) in png_options.iter() {
+            let options = bmfa::WriteOptions {
+                archive_compression: archive_compression,
+                compression_level: compression_level,
+                png_compression: png_compression,
+                png_filter: png_filter,
+                pretty_json: false,
+            };
+            let mut cursor = io::Cursor::new(vec![]);
+            bmfa::to_writer_with_options(&mut cursor, &expected_atlas, &options).unwrap();
+            let result_atlas = bmfa::from_reader(io::Cursor::new(cursor.into_inner())).unwrap();
+
+            assert_eq!(result_atlas.metadata(), expected_atlas.metadata());
+            assert_eq!(result_atlas.pages, expected_atlas.pages);
+        }
+    }
+}
+
+/// A compression level the compression method does not support should be rejected.
+#[test]
+fn bmfa_file_written_with_unsupported_compression_level_should_fail() {
+    let font_atlas = bmfa::load(SAMPLE_FILE).unwrap();
+    let invalid_options = [
+        (bmfa::ArchiveCompression::Stored, Some(1)),
+        (bmfa::ArchiveCompression::Deflated, Some(10)),
+        (bmfa::ArchiveCompression::Deflated, Some(-1)),
+    ];
+    for &(archive_compression, compression_level) in invalid_options.iter() {
+        let options = bmfa::WriteOptions {
+            archive_compression: archive_compression,
+            compression_level: compression_level,
+            ..bmfa::WriteOptions::default()
+        };
+        let mut cursor = io::Cursor::new(vec![]);
+        let result = bmfa::to_writer_with_options(&mut cursor, &font_atlas, &options);
+
+        assert_eq!(result.err().map(|e| e.kind()), Some(bmfa::ErrorKind::InvalidWriteOptions));
+    }
+}
+
+/// Writing to a file with options should produce a file that reads back unchanged.
+#[test]
+fn bmfa_file_written_to_disk_with_options_should_match() {
+    let expected_atlas = bmfa::load(SAMPLE_FILE).unwrap();
+    let options = bmfa::WriteOptions {
+        archive_compression: bmfa::ArchiveCompression::Deflated,
+        ..bmfa::WriteOptions::default()
+    };
+    let path = std::env::temp_dir().join("bmfa_write_options.bmfa");
+    bmfa::write_to_file_with_options(&path, &expected_atlas, &options).unwrap();
+    let result_atlas = bmfa::load(&path).unwrap();
+
+    assert_eq!(result_atlas.pages, expected_atlas.pages);
+}
+
+/// Writing the same atlas twice should produce byte-identical archives, even when the
+/// atlas is loaded again in between.
+#[test]
+fn bmfa_file_written_twice_should_be_identical() {
+    let first = written_sample_with_options(&bmfa::WriteOptions::default());
+    let second = written_sample_with_options(&bmfa::WriteOptions::default());
+
+    assert_eq!(first, second);
+}
+
+/// The glyph entries of the written metadata should be stored in code point order.
+#[test]
+fn bmfa_file_written_should_store_glyphs_in_code_point_order() {
+    let archive = written_sample_with_options(&bmfa::WriteOptions::default());
+    let mut zip_file = zip::ZipArchive::new(io::Cursor::new(archive)).unwrap();
+    let mut metadata = String::new();
+    io::Read::read_to_string(&mut zip_file.by_name("metadata.json").unwrap(), &mut metadata).unwrap();
+    let glyphs = &metadata[metadata.find("\"glyph_metadata\"").unwrap()..];
+    let code_points: Vec<usize> = glyphs.lines()
+        .filter_map(|line| line.strip_prefix("    \""))
+        .map(|line| line[..line.find('"').unwrap()].parse().unwrap())
+        .collect();
+
+    assert!(!code_points.is_empty());
+    assert!(code_points.windows(2).all(|pair| pair[0] < pair[1]));
+}
+
+/// Looking up a character should return its glyph, if the atlas has one.
+#[test]
+fn glyph_should_look_up_a_character() {
+    let font_atlas = bmfa::load(SAMPLE_FILE).unwrap();
+
+    assert_eq!(font_atlas.glyph('A'), font_atlas.glyph_metadata.get(&('A' as usize)));
+    assert_eq!(font_atlas.glyph('\u{4e2d}'), None);
+}
+
+/// Looking up a missing character should return the glyph of the first fallback character
+/// the atlas has.
+#[test]
+fn glyph_or_fallback_should_try_the_fallbacks_in_order() {
+    let font_atlas = bmfa::load(SAMPLE_FILE).unwrap();
+    let question_mark = font_atlas.glyph('?');
+
+    assert_eq!(font_atlas.glyph_or_fallback('A', &bmfa::DEFAULT_FALLBACKS), font_atlas.glyph('A'));
+    assert_eq!(font_atlas.glyph_or_fallback('\u{4e2d}', &bmfa::DEFAULT_FALLBACKS), question_mark);
+    assert_eq!(font_atlas.glyph_or_fallback('\u{4e2d}', &['\u{4e00}', '*', '?']), font_atlas.glyph('*'));
+    assert_eq!(font_atlas.glyph_or_fallback('\u{4e2d}', &[]), None);
+}
+
+/// Looking up a missing character without any fallback character in the atlas should
+/// return the `.notdef` glyph of the atlas.
+#[test]
+fn glyph_or_fallback_should_end_with_the_notdef_glyph() {
+    let mut font_atlas = bmfa::load(SAMPLE_FILE).unwrap();
+    font_atlas.notdef = Some(0x7f);
+
+    assert_eq!(font_atlas.glyph_or_fallback('\u{4e2d}', &['\u{4e00}']), font_atlas.glyph('\u{7f}'));
+    assert_eq!(font_atlas.glyph_or_fallback('\u{4e2d}', &['?']), font_atlas.glyph('?'));
+}
+
+/// The `.notdef` glyph of an atlas should survive being written out and read back in.
+#[test]
+fn bmfa_file_with_notdef_written_and_then_read_should_match() {
+    let mut expected_atlas = bmfa::load(SAMPLE_FILE).unwrap();
+    expected_atlas.notdef = Some(0x7f);
+    let mut cursor = io::Cursor::new(vec![]);
+    bmfa::to_writer(&mut cursor, &expected_atlas).unwrap();
+    let result_atlas = bmfa::from_reader(&mut cursor).unwrap();
+
+    assert_eq!(result_atlas.notdef, Some(0x7f));
+}
+
+/// Converting an image to another origin should reverse its rows, and converting it back
+/// should restore it.
+#[test]
+fn image_converted_to_another_origin_should_reverse_its_rows() {
+    let font_atlas = bmfa::load(SAMPLE_FILE).unwrap();
+    let image = font_atlas.pages[0].clone();
+    let mut converted = image.clone();
+    converted.convert_origin(bmfa::Origin::TopLeft);
+
+    assert_eq!(converted.origin(), bmfa::Origin::TopLeft);
+    let height = image.height();
+    for y in (0..height).step_by(97) {
+        for x in (0..image.width()).step_by(89) {
+            assert_eq!(converted.pixel(x, y), image.pixel(x, height - y - 1));
+        }
+    }
+
+    converted.convert_origin(bmfa::Origin::BottomLeft);
+    assert_eq!(converted, image);
+}
+
+/// Converting an image to the origin it already has should leave it unchanged.
+#[test]
+fn image_converted_to_its_own_origin_should_not_change() {
+    let font_atlas = bmfa::load(SAMPLE_FILE).unwrap();
+    let mut image = font_atlas.pages[0].clone();
+    image.convert_origin(bmfa::Origin::BottomLeft);
+
+    assert_eq!(image, font_atlas.pages[0]);
+}
+
+/// An atlas converted to another origin should be written out as the same PNG image and
+/// read back in unchanged.
+#[test]
+fn bmfa_file_converted_to_another_origin_written_and_then_read_should_match() {
+    let mut expected_atlas = bmfa::load(SAMPLE_FILE).unwrap();
+    expected_atlas.convert_origin(bmfa::Origin::TopLeft);
+    let mut cursor = io::Cursor::new(vec![]);
+    bmfa::to_writer(&mut cursor, &expected_atlas).unwrap();
+    let converted_archive = cursor.into_inner();
+    let result_atlas = bmfa::from_reader(io::Cursor::new(&converted_archive)).unwrap();
+
+    assert_eq!(result_atlas.metadata(), expected_atlas.metadata());
+    assert_eq!(result_atlas.pages, expected_atlas.pages);
+
+    let read_png = |archive: &[u8]| {
+        let mut zip_file = zip::ZipArchive::new(io::Cursor::new(archive)).unwrap();
+        let mut png = vec![];
+        io::Read::read_to_end(&mut zip_file.by_name("atlas.png").unwrap(), &mut png).unwrap();
+        png
+    };
+    assert_eq!(read_png(&converted_archive), read_png(&written_sample()));
+}
+
+/// Converting a packed atlas to another origin and back should restore it.
+#[test]
+fn packed_atlas_converted_there_and_back_should_match() {
+    let expected_atlas = bmfa::bmfont::load("samples/bmfont/freemono.fnt").unwrap();
+    let mut result_atlas = bmfa::bmfont::load("samples/bmfont/freemono.fnt").unwrap();
+    result_atlas.convert_origin(bmfa::Origin::BottomLeft);
+    assert_eq!(result_atlas.validate(), vec![]);
+    result_atlas.convert_origin(bmfa::Origin::TopLeft);
+
+    assert_eq!(result_atlas.pages, expected_atlas.pages);
+    for (code_point, glyph) in expected_atlas.glyph_metadata.iter() {
+        assert!(f32::abs(result_atlas.glyph_metadata[code_point].y_min - glyph.y_min) < 1e-6);
+    }
+}
+
+/// The pixel rectangle of a glyph in the sample atlas should lie in the top left corner of
+/// its glyph slot.
+#[test]
+fn glyph_rect_should_locate_the_glyph_in_its_slot() {
+    let font_atlas = bmfa::load(SAMPLE_FILE).unwrap();
+    let glyph = font_atlas.glyph_metadata[&('A' as usize)];
+    let rect = font_atlas.glyph_rect('A' as usize).unwrap();
+
+    assert_eq!((rect.x, rect.y), (glyph.column * 64, glyph.row * 64));
+    assert_eq!(rect.width, f32::round(glyph.width * 64.0) as usize);
+    assert_eq!(rect.height, f32::round(glyph.height * 64.0) as usize);
+    assert!(rect.width <= 64 && rect.height <= 64);
+    assert_eq!(font_atlas.glyph_rect(0x4e2d), None);
+}
+
+/// The texture coordinates of a glyph should be its pixel rectangle normalized by the page
+/// dimensions, with `v` measured from the top or the bottom of the page.
+#[test]
+fn glyph_uv_rect_should_normalize_the_pixel_rectangle_for_either_origin() {
+    let font_atlas = bmfa::load(SAMPLE_FILE).unwrap();
+    let rect = font_atlas.glyph_rect('A' as usize).unwrap();
+    let top_left = font_atlas.glyph_uv_rect('A' as usize, bmfa::Origin::TopLeft).unwrap();
+    let bottom_left = font_atlas.glyph_uv_rect('A' as usize, bmfa::Origin::BottomLeft).unwrap();
+
+    assert_eq!(top_left.x, rect.x as f32 / 1024.0);
+    assert_eq!(top_left.y, rect.y as f32 / 1024.0);
+    assert_eq!(top_left.width, rect.width as f32 / 1024.0);
+    assert_eq!(top_left.height, rect.height as f32 / 1024.0);
+    assert_eq!(bottom_left.x, top_left.x);
+    assert_eq!(bottom_left.y, 1.0 - (rect.y + rect.height) as f32 / 1024.0);
+    assert_eq!((bottom_left.width, bottom_left.height), (top_left.width, top_left.height));
+    assert_eq!(font_atlas.glyph_uv_rect(0x4e2d, bmfa::Origin::TopLeft), None);
+}
+
+/// The texture coordinates in the convention of the atlas origin should select the pixels
+/// of the glyph from the page as it is stored in memory, without flipping it.
+#[test]
+fn glyph_uv_rect_for_the_atlas_origin_should_select_the_glyph_pixels_in_memory() {
+    let font_atlas = bmfa::load(SAMPLE_FILE).unwrap();
+    let page = &font_atlas.pages[0];
+    let mut png_page = page.clone();
+    png_page.convert_origin(bmfa::Origin::TopLeft);
+    let rect = font_atlas.glyph_rect('g' as usize).unwrap();
+    let uv_rect = font_atlas.glyph_uv_rect('g' as usize, font_atlas.origin).unwrap();
+    let (u, v) = ((uv_rect.x * 1024.0) as usize, (uv_rect.y * 1024.0) as usize);
+
+    // The sample atlas has a bottom left origin, so the memory rows count upwards from the
+    // bottom of the glyph.
+    assert_eq!(font_atlas.origin, bmfa::Origin::BottomLeft);
+    for j in 0..rect.height {
+        for i in 0..rect.width {
+            assert_eq!(page.pixel(u + i, v + j), png_page.pixel(rect.x + i, rect.y + rect.height - 1 - j));
+        }
+    }
+}
+
+/// The pixel rectangle of a glyph in a packed atlas should be the rectangle it records.
+#[test]
+fn glyph_rect_in_a_packed_atlas_should_be_the_recorded_rectangle() {
+    let font_atlas = bmfa::bmfont::load("samples/bmfont/freemono.fnt").unwrap();
+
+    for (code_point, glyph) in font_atlas.glyph_metadata.iter() {
+        assert_eq!(font_atlas.glyph_rect(*code_point), glyph.rect);
+    }
 }
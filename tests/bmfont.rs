@@ -0,0 +1,563 @@
+use bmfa::bmfont;
+use bmfa::glyph_image::GlyphBounds;
+use bmfa::{AtlasLayout, ErrorKind, Origin, PixelFormat};
+
+const SAMPLE_FILE: &str = "samples/freemono.bmfa";
+const SAMPLE_TEXT_FILE: &str = "samples/bmfont/freemono.fnt";
+const SAMPLE_XML_FILE: &str = "samples/bmfont/freemono_xml.fnt";
+const SAMPLE_BINARY_FILE: &str = "samples/bmfont/freemono_binary.fnt";
+
+// The DejaVu Sans descriptors are laid out the way AngelCode BMFont writes them, with
+// padded columns, CRLF line endings, the channel attributes of the common block and the
+// glyph rectangles packed tightly onto two pages. They were written from FreeType renderings
+// of the sample font, independently of the exporter of this crate.
+const DEJAVU_TEXT_FILE: &str = "samples/bmfont/dejavu_sans.fnt";
+const DEJAVU_XML_FILE: &str = "samples/bmfont/dejavu_sans_xml.fnt";
+const DEJAVU_BINARY_FILE: &str = "samples/bmfont/dejavu_sans_binary.fnt";
+
+const SMALL_TEXT_FONT: &str = "\
+info face=\"Small Font\" size=-16 bold=0 italic=0 padding=1,2,3,4 spacing=1,1
+common lineHeight=20 base=15 scaleW=64 scaleH=32 pages=2 packed=0
+page id=0 file=\"small_0.png\"
+page id=1 file=\"small_1.png\"
+chars count=2
+char id=65   x=0  y=0  width=10 height=12 xoffset=1 yoffset=3 xadvance=11 page=0 chnl=15
+char id=86   x=10 y=0  width=10 height=14 xoffset=-1 yoffset=2 xadvance=10 page=1 chnl=15
+kernings count=1
+kerning first=65 second=86 amount=-2
+";
+
+const SMALL_XML_FONT: &str = r#"<?xml version="1.0"?>
+<!-- Generated by hand. -->
+<font>
+  <info face="Small &amp; Font" size="-16" padding="1,2,3,4" spacing="1,1"/>
+  <common lineHeight="20" base="15" scaleW="64" scaleH="32" pages="2" packed="0"/>
+  <pages>
+    <page id="1" file="small_1.png" />
+    <page id="0" file="small_0.png" />
+  </pages>
+  <chars count="2">
+    <char id="65" x="0" y="0" width="10" height="12" xoffset="1" yoffset="3" xadvance="11" page="0" chnl="15" />
+    <char id="86" x="10" y="0" width="10" height="14" xoffset="-1" yoffset="2" xadvance="10" page="1" chnl="15" />
+  </chars>
+  <kernings count="1">
+    <kerning first="65" second="86" amount="-2" />
+  </kernings>
+</font>
+"#;
+
+
+/// The text variant of the small font should parse into its blocks.
+#[test]
+fn parse_should_read_a_text_descriptor() {
+    let font = bmfont::parse(SMALL_TEXT_FONT.as_bytes()).unwrap();
+
+    assert_eq!(font.info.face, "Small Font");
+    assert_eq!(font.info.size, -16);
+    assert_eq!(font.info.padding, [1, 2, 3, 4]);
+    assert_eq!(font.info.spacing, [1, 1]);
+    assert_eq!(font.common, bmfont::BmFontCommon::new(20, 15, 64, 32));
+    assert_eq!(font.pages, vec![String::from("small_0.png"), String::from("small_1.png")]);
+    assert_eq!(font.chars.len(), 2);
+    assert_eq!(font.chars[1].x_offset, -1);
+    assert_eq!(font.chars[1].page, 1);
+    assert_eq!(font.kernings.len(), 1);
+    assert_eq!(font.kernings[0].amount, -2);
+}
+
+/// The XML variant of the small font should parse into the same blocks as the text
+/// variant, with the pages ordered by their ids and the XML entities replaced.
+#[test]
+fn parse_should_read_an_xml_descriptor() {
+    let text_font = bmfont::parse(SMALL_TEXT_FONT.as_bytes()).unwrap();
+    let xml_font = bmfont::parse(SMALL_XML_FONT.as_bytes()).unwrap();
+
+    assert_eq!(xml_font.info.face, "Small & Font");
+    assert_eq!(xml_font.common, text_font.common);
+    assert_eq!(xml_font.pages, text_font.pages);
+    assert_eq!(xml_font.chars, text_font.chars);
+    assert_eq!(xml_font.kernings, text_font.kernings);
+}
+
+/// A descriptor without a common block has no line metrics, so it cannot be read.
+#[test]
+fn parse_should_reject_a_descriptor_without_a_common_block() {
+    let result = bmfont::parse(b"info face=\"Small Font\" size=16\n");
+
+    assert_eq!(result.err().map(|e| e.kind()), Some(ErrorKind::CannotLoadBmFont));
+}
+
+/// A character missing one of its required attributes should be rejected.
+#[test]
+fn parse_should_reject_a_character_missing_an_attribute() {
+    let descriptor = "common lineHeight=20 base=15 scaleW=64 scaleH=32\nchar id=65 x=0 y=0 width=10\n";
+    let result = bmfont::parse(descriptor.as_bytes());
+
+    assert_eq!(result.err().map(|e| e.kind()), Some(ErrorKind::CannotLoadBmFont));
+}
+
+/// A page id past the page count of the common block should be rejected instead of
+/// allocating a page table for it.
+#[test]
+fn parse_should_reject_a_page_id_out_of_range() {
+    for descriptor in [
+        "common lineHeight=20 base=15 scaleW=64 scaleH=32 pages=1\npage id=4000000000 file=\"x.png\"\n",
+        "common lineHeight=20 base=15 scaleW=64 scaleH=32 pages=1\npage id=1 file=\"x.png\"\n",
+        "page id=4000000000 file=\"x.png\"\ncommon lineHeight=20 base=15 scaleW=64 scaleH=32\n",
+    ].iter() {
+        let result = bmfont::parse(descriptor.as_bytes());
+
+        assert_eq!(result.err().map(|e| e.kind()), Some(ErrorKind::CannotLoadBmFont));
+    }
+}
+
+/// The glyph BMFont writes for invalid characters has the id -1, and should be skipped
+/// instead of failing to parse the whole descriptor.
+#[test]
+fn parse_should_skip_the_invalid_character_glyph() {
+    let text = SMALL_TEXT_FONT.replace(
+        "chars count=2\n",
+        "chars count=3\nchar id=-1 x=20 y=0 width=8 height=12 xoffset=0 yoffset=3 xadvance=9 page=0 chnl=15\n"
+    );
+    let xml = SMALL_XML_FONT.replace(
+        "<chars count=\"2\">\n",
+        "<chars count=\"3\">\n    <char id=\"-1\" x=\"20\" y=\"0\" width=\"8\" height=\"12\" xoffset=\"0\" \
+        yoffset=\"3\" xadvance=\"9\" page=\"0\" chnl=\"15\" />\n"
+    );
+    let expected = bmfont::parse(SMALL_TEXT_FONT.as_bytes()).unwrap();
+
+    assert_eq!(bmfont::parse(text.as_bytes()).unwrap().chars, expected.chars);
+    assert_eq!(bmfont::parse(xml.as_bytes()).unwrap().chars, expected.chars);
+}
+
+/// A binary descriptor that ends in the middle of a block should be rejected.
+#[test]
+fn parse_should_reject_a_truncated_binary_descriptor() {
+    let data = std::fs::read(SAMPLE_BINARY_FILE).unwrap();
+    let result = bmfont::parse(&data[..(data.len() - 3)]);
+
+    assert_eq!(result.err().map(|e| e.kind()), Some(ErrorKind::CannotLoadBmFont));
+}
+
+/// The text, XML and binary variants of the sample descriptor should parse identically.
+#[test]
+fn parse_should_read_all_sample_descriptor_variants_identically() {
+    let text_font = bmfont::parse(&std::fs::read(SAMPLE_TEXT_FILE).unwrap()).unwrap();
+    let xml_font = bmfont::parse(&std::fs::read(SAMPLE_XML_FILE).unwrap()).unwrap();
+    let binary_font = bmfont::parse(&std::fs::read(SAMPLE_BINARY_FILE).unwrap()).unwrap();
+
+    assert_eq!(text_font, xml_font);
+    assert_eq!(text_font, binary_font);
+}
+
+/// Return the character of a descriptor with the given id.
+fn find_char(font: &bmfont::BmFont, id: char) -> bmfont::BmFontChar {
+    *font.chars.iter().find(|ch| ch.id == id as usize).unwrap()
+}
+
+/// Every variant of the DejaVu Sans descriptor should parse into the values stored in it,
+/// skipping the attributes the importer has no use for.
+#[test]
+fn parse_should_read_the_dejavu_sans_descriptor_variants() {
+    for path in [DEJAVU_TEXT_FILE, DEJAVU_XML_FILE, DEJAVU_BINARY_FILE].iter() {
+        let font = bmfont::parse(&std::fs::read(path).unwrap()).unwrap();
+
+        assert_eq!(font.info.face, "DejaVu Sans");
+        assert_eq!(font.info.size, 24);
+        assert_eq!(font.info.padding, [0, 0, 0, 0]);
+        assert_eq!(font.info.spacing, [1, 1]);
+        assert_eq!(font.common, bmfont::BmFontCommon::new(28, 23, 128, 128));
+        assert_eq!(font.pages, vec![String::from("dejavu_sans_0.png"), String::from("dejavu_sans_1.png")]);
+        assert_eq!(font.chars.len(), 95);
+        assert_eq!(find_char(&font, 'A'), bmfont::BmFontChar {
+            id: 'A' as usize, x: 110, y: 46, width: 17, height: 18, x_offset: 0, y_offset: 5, x_advance: 16, page: 0,
+        });
+        assert_eq!(find_char(&font, 'g'), bmfont::BmFontChar {
+            id: 'g' as usize, x: 24, y: 0, width: 13, height: 18, x_offset: 1, y_offset: 10, x_advance: 15, page: 1,
+        });
+        assert_eq!(find_char(&font, 'j'), bmfont::BmFontChar {
+            id: 'j' as usize, x: 12, y: 0, width: 6, height: 23, x_offset: -1, y_offset: 5, x_advance: 7, page: 0,
+        });
+        assert_eq!(font.kernings.len(), 158);
+        assert!(font.kernings.contains(&bmfont::BmFontKerning { first: 'A' as usize, second: 'V' as usize, amount: -1 }));
+        assert!(font.kernings.contains(&bmfont::BmFontKerning { first: 'T' as usize, second: 'o' as usize, amount: -4 }));
+    }
+}
+
+/// Importing the DejaVu Sans font should produce a two page packed atlas whose glyphs
+/// cover their tight character rectangles and sit where the descriptor places them.
+#[test]
+fn load_should_import_the_dejavu_sans_font() {
+    let text_atlas = bmfont::load(DEJAVU_TEXT_FILE).unwrap();
+    let xml_atlas = bmfont::load(DEJAVU_XML_FILE).unwrap();
+    let binary_atlas = bmfont::load(DEJAVU_BINARY_FILE).unwrap();
+    assert_eq!(xml_atlas.metadata(), text_atlas.metadata());
+    assert_eq!(binary_atlas.metadata(), text_atlas.metadata());
+
+    let atlas = text_atlas;
+    assert_eq!(atlas.layout, AtlasLayout::Packed);
+    assert_eq!((atlas.width, atlas.height), (128, 128));
+    assert_eq!(atlas.pages.len(), 2);
+    assert_eq!(atlas.pages[1].format(), PixelFormat::Rgba8);
+    assert_eq!(atlas.glyph_size, 24);
+    assert!(f32::abs(atlas.ascent * 24.0 - 23.0) < 1e-3);
+    assert!(f32::abs(atlas.line_height() * 24.0 - 28.0) < 1e-3);
+    assert!(f32::abs(atlas.kern('T' as usize, 'o' as usize) * 24.0 + 4.0) < 1e-3);
+
+    let g = atlas.glyph_metadata[&('g' as usize)];
+    assert_eq!(g.page, 1);
+    assert_eq!(atlas.glyph_rect('g' as usize), Some(bmfa::GlyphRect::new(24, 0, 13, 18)));
+    // The descender of `g` reaches 23 - (10 + 18) = -5 pixels below the baseline.
+    assert!(f32::abs(g.y_offset * 24.0 + 5.0) < 1e-3);
+    assert!(f32::abs(g.x_offset * 24.0 - 1.0) < 1e-3);
+    assert!(f32::abs(g.advance * 24.0 - 15.0) < 1e-3);
+
+    for &ch in ['A', 'g', 'j'].iter() {
+        let slot = atlas.glyph_view(ch as usize, GlyphBounds::Slot).unwrap();
+        let tight = atlas.glyph_view(ch as usize, GlyphBounds::Tight).unwrap();
+        assert_eq!(tight.rect(), slot.rect());
+        assert!(slot.rows().all(|row| row.chunks(4).all(|pixel| pixel[..3] == [255, 255, 255])));
+    }
+}
+
+/// An imported font should be a single page packed atlas with a top left origin, with
+/// every character as a glyph.
+#[test]
+fn load_should_import_the_sample_font_as_a_packed_atlas() {
+    let font = bmfont::parse(&std::fs::read(SAMPLE_TEXT_FILE).unwrap()).unwrap();
+    let atlas = bmfont::load(SAMPLE_TEXT_FILE).unwrap();
+
+    assert_eq!(atlas.origin, Origin::TopLeft);
+    assert_eq!(atlas.layout, AtlasLayout::Packed);
+    assert_eq!(atlas.pages.len(), 1);
+    assert_eq!(atlas.width, 1024);
+    assert_eq!(atlas.height, 1024);
+    assert_eq!(atlas.pages[0].len_bytes(), 4 * 1024 * 1024);
+    assert_eq!(atlas.glyph_metadata.len(), font.chars.len());
+    assert_eq!(atlas.slot_glyph_size, atlas.glyph_size + atlas.padding);
+}
+
+/// Every imported glyph should keep the pixel rectangle of its character.
+#[test]
+fn load_should_keep_the_character_rectangles() {
+    let font = bmfont::parse(&std::fs::read(SAMPLE_TEXT_FILE).unwrap()).unwrap();
+    let atlas = bmfont::load(SAMPLE_TEXT_FILE).unwrap();
+
+    for ch in font.chars.iter() {
+        let rect = atlas.glyph_metadata[&ch.id].rect.unwrap();
+        assert_eq!((rect.x, rect.y, rect.width, rect.height), (ch.x, ch.y, ch.width, ch.height));
+    }
+}
+
+/// The line metrics of an imported atlas should reproduce the line height and baseline
+/// of the descriptor.
+#[test]
+fn load_should_map_the_common_line_metrics() {
+    let font = bmfont::parse(&std::fs::read(SAMPLE_TEXT_FILE).unwrap()).unwrap();
+    let atlas = bmfont::load(SAMPLE_TEXT_FILE).unwrap();
+    let slot = atlas.slot_glyph_size as f32;
+
+    assert!(f32::abs(atlas.line_height() * slot - font.common.line_height as f32) < 1e-3);
+    assert!(f32::abs(atlas.ascent * slot - font.common.base as f32) < 1e-3);
+    assert_eq!(atlas.font_size, atlas.glyph_size as f32);
+}
+
+/// The glyph metrics of an imported atlas should place every character at the position
+/// the descriptor places it relative to the top of the line.
+#[test]
+fn load_should_map_the_character_metrics() {
+    let font = bmfont::parse(&std::fs::read(SAMPLE_TEXT_FILE).unwrap()).unwrap();
+    let atlas = bmfont::load(SAMPLE_TEXT_FILE).unwrap();
+    let slot = atlas.slot_glyph_size as f32;
+
+    for ch in font.chars.iter() {
+        let glyph = &atlas.glyph_metadata[&ch.id];
+        let top = font.common.base as f32 - (glyph.y_offset * slot + ch.height as f32);
+        assert!(f32::abs(top - ch.y_offset as f32) < 1e-3);
+        assert!(f32::abs(glyph.x_offset * slot - ch.x_offset as f32) < 1e-3);
+        assert!(f32::abs(glyph.advance * slot - ch.x_advance as f32) < 1e-3);
+    }
+}
+
+/// The kerning pairs of the descriptor should end up in the kerning table of the atlas.
+#[test]
+fn load_should_map_the_kerning_pairs() {
+    let atlas = bmfont::load(SAMPLE_TEXT_FILE).unwrap();
+    let slot = atlas.slot_glyph_size as f32;
+
+    assert_eq!(atlas.kerning.len(), 2);
+    assert!(f32::abs(atlas.kern('A' as usize, 'V' as usize) * slot + 3.0) < 1e-3);
+    assert_eq!(atlas.kern('V' as usize, 'A' as usize), 0.0);
+}
+
+/// Importing the text, XML and binary variants of the sample font should produce the
+/// same atlas.
+#[test]
+fn load_should_import_all_sample_descriptor_variants_identically() {
+    let text_atlas = bmfont::load(SAMPLE_TEXT_FILE).unwrap();
+    let xml_atlas = bmfont::load(SAMPLE_XML_FILE).unwrap();
+    let binary_atlas = bmfont::load(SAMPLE_BINARY_FILE).unwrap();
+
+    assert_eq!(text_atlas.metadata(), xml_atlas.metadata());
+    assert_eq!(text_atlas.metadata(), binary_atlas.metadata());
+    assert_eq!(text_atlas.pages, binary_atlas.pages);
+}
+
+/// The sample font was made from the pages of the sample atlas, so the imported page
+/// should hold the same pixels as the sample atlas stored top down.
+#[test]
+fn load_should_import_the_page_images() {
+    let atlas = bmfont::load(SAMPLE_TEXT_FILE).unwrap();
+    let sample = bmfa::load(SAMPLE_FILE).unwrap();
+    let sample_page = &sample.pages[0];
+    let width_in_bytes = 4 * sample_page.width();
+    let height = sample_page.height();
+    let mut expected = sample_page.as_ref().to_vec();
+    for row in 0..(height / 2) {
+        for i in 0..width_in_bytes {
+            expected.swap(row * width_in_bytes + i, (height - row - 1) * width_in_bytes + i);
+        }
+    }
+
+    assert_eq!(atlas.pages[0].as_ref(), expected.as_slice());
+}
+
+/// A descriptor naming a page image that does not exist should fail to load.
+#[test]
+fn load_should_fail_when_a_page_image_is_missing() {
+    let directory = std::env::temp_dir().join("bmfa_bmfont_missing_page");
+    std::fs::create_dir_all(&directory).unwrap();
+    let path = directory.join("small.fnt");
+    std::fs::write(&path, SMALL_TEXT_FONT).unwrap();
+    let result = bmfont::load(&path);
+
+    assert_eq!(result.err().map(|e| e.kind()), Some(ErrorKind::FontAtlasImageNotFound));
+}
+
+/// A character stored on a page the descriptor does not name should be rejected.
+#[test]
+fn from_bmfont_should_reject_a_character_on_a_missing_page() {
+    let mut font = bmfont::parse(SMALL_TEXT_FONT.as_bytes()).unwrap();
+    font.chars[1].page = 2;
+    let pages = vec![
+        bmfa::BitmapFontAtlasImage::new(vec![0; 4 * 64 * 32], 64, 32, PixelFormat::Rgba8, Origin::TopLeft),
+        bmfa::BitmapFontAtlasImage::new(vec![0; 4 * 64 * 32], 64, 32, PixelFormat::Rgba8, Origin::TopLeft),
+    ];
+    let result = bmfont::from_bmfont(&font, pages);
+
+    assert_eq!(result.err().map(|e| e.kind()), Some(ErrorKind::CannotLoadBmFont));
+}
+
+/// Return blank pages matching the page size and count of a descriptor.
+fn blank_pages(font: &bmfont::BmFont) -> Vec<bmfa::BitmapFontAtlasImage> {
+    let (width, height) = (font.common.scale_width, font.common.scale_height);
+
+    font.pages.iter()
+        .map(|_| bmfa::BitmapFontAtlasImage::new(vec![0; 4 * width * height], width, height, PixelFormat::Rgba8, Origin::TopLeft))
+        .collect()
+}
+
+/// Character positions and offsets at the limits of their types should be rejected instead
+/// of overflowing.
+#[test]
+fn from_bmfont_should_reject_extreme_text_character_values() {
+    let extremes = [
+        ("x=0  y=0  width=10", format!("x={} y=0  width=2", usize::MAX)),
+        ("x=0  y=0  width=10 height=12", format!("x=0  y={} width=10 height=2", usize::MAX)),
+        ("yoffset=3", format!("yoffset={}", isize::MAX)),
+        ("yoffset=3", format!("yoffset={}", isize::MIN)),
+        ("base=15", format!("base={}", usize::MAX)),
+    ];
+    for (from, to) in extremes.iter() {
+        let font = bmfont::parse(SMALL_TEXT_FONT.replacen(from, to, 1).as_bytes()).unwrap();
+        let result = bmfont::from_bmfont(&font, blank_pages(&font));
+
+        assert_eq!(result.err().map(|e| e.kind()), Some(ErrorKind::CannotLoadBmFont));
+    }
+}
+
+/// A binary character at the largest position and size the format can store should be
+/// rejected as lying outside of its page.
+#[test]
+fn from_bmfont_should_reject_extreme_binary_character_values() {
+    let mut data = std::fs::read(SAMPLE_BINARY_FILE).unwrap();
+    // Skip the header and the blocks in front of the characters block.
+    let mut offset = 4;
+    while data[offset] != 4 {
+        let block_size = u32::from_le_bytes([data[offset + 1], data[offset + 2], data[offset + 3], data[offset + 4]]);
+        offset += 5 + block_size as usize;
+    }
+    // The position and the size of the first character follow its id.
+    for byte in data[(offset + 9)..(offset + 17)].iter_mut() {
+        *byte = 0xff;
+    }
+    let font = bmfont::parse(&data).unwrap();
+    let result = bmfont::from_bmfont(&font, blank_pages(&font));
+
+    assert_eq!(font.chars[0].x, 0xffff);
+    assert_eq!(result.err().map(|e| e.kind()), Some(ErrorKind::CannotLoadBmFont));
+}
+
+/// A page whose dimensions differ from the page size of the descriptor should be rejected.
+#[test]
+fn from_bmfont_should_reject_pages_of_the_wrong_size() {
+    let font = bmfont::parse(SMALL_TEXT_FONT.as_bytes()).unwrap();
+    let pages = vec![
+        bmfa::BitmapFontAtlasImage::new(vec![0; 4 * 64 * 32], 64, 32, PixelFormat::Rgba8, Origin::TopLeft),
+        bmfa::BitmapFontAtlasImage::new(vec![0; 4 * 32 * 32], 32, 32, PixelFormat::Rgba8, Origin::TopLeft),
+    ];
+    let result = bmfont::from_bmfont(&font, pages);
+
+    assert_eq!(result.err().map(|e| e.kind()), Some(ErrorKind::CannotLoadAtlasImage));
+}
+
+/// Export the sample atlas to a fresh directory and import it again.
+fn round_trip_sample(directory_name: &str, format: bmfont::BmFontFormat) -> (bmfa::BitmapFontAtlas, bmfa::BitmapFontAtlas) {
+    let atlas = bmfa::load(SAMPLE_FILE).unwrap();
+    let directory = std::env::temp_dir().join(directory_name);
+    std::fs::create_dir_all(&directory).unwrap();
+    let path = directory.join("freemono.fnt");
+    bmfont::write_to_file(&path, &atlas, format).unwrap();
+    let imported = bmfont::load(&path).unwrap();
+
+    (atlas, imported)
+}
+
+/// Converting an atlas to a descriptor should produce one character per glyph and name
+/// one page image per atlas page.
+#[test]
+fn to_bmfont_should_produce_one_character_per_glyph() {
+    let atlas = bmfa::load(SAMPLE_FILE).unwrap();
+    let font = bmfont::to_bmfont(&atlas, "freemono");
+
+    assert_eq!(font.chars.len(), atlas.glyph_metadata.len());
+    assert_eq!(font.pages, vec![String::from("freemono_0.png")]);
+    assert_eq!(font.common.scale_width, atlas.width);
+    assert_eq!(font.common.scale_height, atlas.height);
+    assert!(font.chars.windows(2).all(|pair| pair[0].id < pair[1].id));
+}
+
+/// The sample descriptor was made from the sample atlas, so converting the sample atlas
+/// should reproduce it.
+#[test]
+fn to_bmfont_should_reproduce_the_sample_descriptor() {
+    let atlas = bmfa::load(SAMPLE_FILE).unwrap();
+    let font = bmfont::to_bmfont(&atlas, "freemono");
+    let sample_font = bmfont::parse(&std::fs::read(SAMPLE_TEXT_FILE).unwrap()).unwrap();
+
+    assert_eq!(font.common, sample_font.common);
+    assert_eq!(font.chars, sample_font.chars);
+}
+
+/// The text writer should produce a descriptor that parses back to the same font.
+#[test]
+fn write_text_should_parse_back_to_the_same_font() {
+    let font = bmfont::parse(SMALL_TEXT_FONT.as_bytes()).unwrap();
+    let mut text = vec![];
+    bmfont::write_text(&mut text, &font).unwrap();
+
+    assert_eq!(bmfont::parse(&text).unwrap(), font);
+}
+
+/// The text variant cannot escape quotes, so the text writer should replace them in the
+/// face name to keep the descriptor readable, and refuse page file names containing them.
+#[test]
+fn write_text_should_keep_quotes_from_breaking_the_descriptor() {
+    let mut font = bmfont::parse(SMALL_TEXT_FONT.as_bytes()).unwrap();
+    font.info.face = String::from("Small \"Quoted\" Font");
+    let mut text = vec![];
+    bmfont::write_text(&mut text, &font).unwrap();
+    let result = bmfont::parse(&text).unwrap();
+
+    assert_eq!(result.info.face, "Small 'Quoted' Font");
+    assert_eq!(result.chars, font.chars);
+
+    font.pages[1] = String::from("small\"1.png");
+    let error = bmfont::write_text(&mut vec![], &font).err();
+    assert_eq!(error.map(|e| e.kind()), Some(std::io::ErrorKind::InvalidInput));
+}
+
+/// The XML writer should escape its attribute values and produce a descriptor that
+/// parses back to the same font.
+#[test]
+fn write_xml_should_parse_back_to_the_same_font() {
+    let mut font = bmfont::parse(SMALL_TEXT_FONT.as_bytes()).unwrap();
+    font.info.face = String::from("Small \"Quoted\" & <Font>");
+    let mut xml = vec![];
+    bmfont::write_xml(&mut xml, &font).unwrap();
+
+    assert_eq!(bmfont::parse(&xml).unwrap(), font);
+}
+
+/// Exporting the sample atlas and importing it again should keep every glyph at the same
+/// position on the page, with the same pixels.
+#[test]
+fn round_trip_should_keep_the_glyph_positions() {
+    for &format in [bmfont::BmFontFormat::Text, bmfont::BmFontFormat::Xml].iter() {
+        let (atlas, imported) = round_trip_sample(&format!("bmfa_bmfont_round_trip_{:?}", format), format);
+
+        assert_eq!(imported.glyph_metadata.len(), atlas.glyph_metadata.len());
+        assert_eq!(imported.width, atlas.width);
+        assert_eq!(imported.height, atlas.height);
+        for (code_point, glyph) in atlas.glyph_metadata.iter() {
+            let imported_glyph = &imported.glyph_metadata[code_point];
+            let rect = imported_glyph.rect.unwrap();
+            assert_eq!(rect.x, glyph.column * atlas.slot_glyph_size);
+            assert_eq!(rect.y, glyph.row * atlas.slot_glyph_size);
+            assert_eq!(imported_glyph.page, glyph.page);
+        }
+        let sample = bmfont::load(SAMPLE_TEXT_FILE).unwrap();
+        assert_eq!(imported.pages, sample.pages);
+    }
+}
+
+/// Converting an atlas with a negative ascent should place the baseline at the top of the
+/// line and keep every glyph where it sits relative to the baseline.
+#[test]
+fn to_bmfont_should_keep_the_glyphs_of_an_atlas_with_negative_ascent() {
+    let mut atlas = bmfa::load(SAMPLE_FILE).unwrap();
+    atlas.ascent = -0.25;
+    let font = bmfont::to_bmfont(&atlas, "freemono");
+    let imported = bmfont::from_bmfont(&font, blank_pages(&font)).unwrap();
+    let slot = atlas.slot_glyph_size as f32;
+
+    assert_eq!(font.common.base, 0);
+    for (code_point, glyph) in atlas.glyph_metadata.iter() {
+        let imported_glyph = &imported.glyph_metadata[code_point];
+        let bottom = f32::round(glyph.y_offset * slot);
+        assert!(f32::abs(imported_glyph.y_offset * imported.slot_glyph_size as f32 - bottom) < 1e-3);
+    }
+}
+
+/// Laying out text with the exported atlas should place the glyphs where the original
+/// atlas places them, up to the rounding to whole pixels.
+#[test]
+fn round_trip_should_keep_the_layout_of_text() {
+    use bmfa::layout::{self, LayoutSettings};
+
+    let (atlas, imported) = round_trip_sample("bmfa_bmfont_round_trip_layout", bmfont::BmFontFormat::Text);
+    let text = "The quick brown fox\njumps over the lazy dog.";
+    let mut settings = LayoutSettings::new(atlas.font_size);
+    settings.y = 100.0;
+    let expected = layout::layout(&atlas, text, &settings);
+    let result = layout::layout(&imported, text, &settings);
+
+    assert_eq!(result.len(), expected.len());
+    for (result, expected) in result.iter().zip(expected.iter()) {
+        assert_eq!(result.code_point, expected.code_point);
+        assert!(f32::abs(result.screen_rect.x - expected.screen_rect.x) < 1.0);
+        assert!(f32::abs(result.screen_rect.y - expected.screen_rect.y) < 1.0);
+        assert!(f32::abs(result.screen_rect.width - expected.screen_rect.width) < 1.0);
+        assert!(f32::abs(result.screen_rect.height - expected.screen_rect.height) < 1.0);
+    }
+}
+
+/// Exporting to a directory that does not exist should fail to create the page files.
+#[test]
+fn write_to_file_in_a_missing_directory_should_fail_to_create_file() {
+    let atlas = bmfa::load(SAMPLE_FILE).unwrap();
+    let path = std::env::temp_dir().join("bmfa_bmfont_missing_directory").join("missing").join("freemono.fnt");
+    let result = bmfont::write_to_file(path, &atlas, bmfont::BmFontFormat::Text);
+
+    assert_eq!(result.err().map(|e| e.kind()), Some(ErrorKind::CannotCreateFile));
+}
@@ -0,0 +1,237 @@
+use bmfa::{BitmapFontAtlasBuilder, ErrorKind, GlyphBitmap, GlyphMetadata, Origin, PixelFormat};
+use std::io;
+
+
+/// Construct a single channel glyph bitmap filled with one value.
+fn filled_bitmap(width: usize, height: usize, value: u8) -> GlyphBitmap {
+    GlyphBitmap::new(vec![value; width * height], width, height)
+}
+
+fn sample_builder() -> BitmapFontAtlasBuilder {
+    let mut descender = filled_bitmap(6, 12, 200);
+    descender.y_offset = -3.0;
+    BitmapFontAtlasBuilder::new(16, 2)
+        .format(PixelFormat::R8)
+        .glyph('A' as usize, filled_bitmap(10, 12, 255))
+        .glyph('g' as usize, descender)
+        .glyph(' ' as usize, GlyphBitmap::new(vec![], 0, 0))
+        .glyph('.' as usize, filled_bitmap(2, 2, 128))
+        .glyph('B' as usize, filled_bitmap(14, 14, 64))
+}
+
+/// A built atlas should satisfy the grid invariants of the bmfa format.
+#[test]
+fn built_atlas_should_satisfy_the_grid_invariants() {
+    let atlas = sample_builder().build().unwrap();
+
+    assert_eq!(atlas.width, atlas.columns * atlas.slot_glyph_size);
+    assert_eq!(atlas.height, atlas.rows * atlas.slot_glyph_size);
+    assert_eq!(atlas.slot_glyph_size, atlas.padding + atlas.glyph_size);
+    assert_eq!(atlas.pages.len(), 1);
+    assert_eq!(atlas.pages[0].len_bytes(), atlas.width * atlas.height);
+}
+
+/// The glyphs should fill the grid in code point order from the top left slot.
+#[test]
+fn built_atlas_should_place_glyphs_in_code_point_order() {
+    let atlas = sample_builder().columns(2).build().unwrap();
+    let mut code_points: Vec<usize> = atlas.glyph_metadata.keys().cloned().collect();
+    code_points.sort();
+
+    assert_eq!((atlas.columns, atlas.rows), (2, 3));
+    for (i, code_point) in code_points.iter().enumerate() {
+        let glyph = &atlas.glyph_metadata[code_point];
+        assert_eq!((glyph.row, glyph.column), (i / 2, i % 2));
+    }
+}
+
+/// Each glyph bitmap should be copied into its slot, inset by half the padding.
+#[test]
+fn built_atlas_should_copy_the_glyph_bitmaps_into_their_slots() {
+    let atlas = sample_builder().origin(Origin::TopLeft).build().unwrap();
+    let page = &atlas.pages[0];
+    let glyph = &atlas.glyph_metadata[&('B' as usize)];
+    let (x, y) = (glyph.column * 16 + 1, glyph.row * 16 + 1);
+
+    assert_eq!(page.pixel(x, y), &[64]);
+    assert_eq!(page.pixel(x + 13, y + 13), &[64]);
+    assert_eq!(page.pixel(x - 1, y - 1), &[0]);
+    assert_eq!(page.pixel(x + 14, y + 14), &[0]);
+}
+
+/// A bottom left atlas should store its rows in the opposite order of a top left atlas.
+#[test]
+fn built_bottom_left_atlas_should_be_flipped() {
+    let top_left = sample_builder().origin(Origin::TopLeft).build().unwrap();
+    let bottom_left = sample_builder().origin(Origin::BottomLeft).build().unwrap();
+    let height = top_left.height;
+
+    for y in 0..height {
+        for x in 0..top_left.width {
+            assert_eq!(top_left.pages[0].pixel(x, y), bottom_left.pages[0].pixel(x, height - y - 1));
+        }
+    }
+}
+
+/// The line metrics should default to the extent of the visible glyphs, normalized by the
+/// slot glyph size.
+#[test]
+fn built_atlas_should_derive_line_metrics_from_the_glyphs() {
+    let atlas = sample_builder().build().unwrap();
+
+    assert_eq!(atlas.ascent, 14.0 / 16.0);
+    assert_eq!(atlas.descent, -3.0 / 16.0);
+    assert_eq!(atlas.line_gap, 0.0);
+    assert_eq!(atlas.font_size, 14.0);
+}
+
+/// Explicit line metrics, advances and kerning should be normalized by the slot glyph size.
+#[test]
+fn built_atlas_should_normalize_the_given_metrics() {
+    let mut bitmap = filled_bitmap(10, 12, 255);
+    bitmap.advance = 12.0;
+    let atlas = BitmapFontAtlasBuilder::new(16, 2)
+        .format(PixelFormat::R8)
+        .font_size(13.5)
+        .line_metrics(12.0, -4.0, 2.0)
+        .glyph('A' as usize, bitmap)
+        .glyph('V' as usize, filled_bitmap(10, 12, 255))
+        .kerning('A' as usize, 'V' as usize, -2.0)
+        .build()
+        .unwrap();
+
+    assert_eq!(atlas.font_size, 13.5);
+    assert_eq!(atlas.line_height(), 18.0 / 16.0);
+    assert_eq!(atlas.glyph_metadata[&('A' as usize)].advance, 12.0 / 16.0);
+    assert_eq!(atlas.kern('A' as usize, 'V' as usize), -2.0 / 16.0);
+}
+
+/// A built atlas should survive being written out and read back in.
+#[test]
+fn built_atlas_written_and_then_read_should_match() {
+    let expected_atlas = sample_builder().build().unwrap();
+    let mut cursor = io::Cursor::new(vec![]);
+    bmfa::to_writer(&mut cursor, &expected_atlas).unwrap();
+    let result_atlas = bmfa::from_reader(&mut cursor).unwrap();
+
+    assert_eq!(result_atlas.metadata(), expected_atlas.metadata());
+    assert_eq!(result_atlas.pages, expected_atlas.pages);
+}
+
+/// Building an atlas with a padding as large as the slot should fail.
+#[test]
+fn building_with_padding_larger_than_the_slot_should_fail() {
+    let result = BitmapFontAtlasBuilder::new(16, 16)
+        .glyph('A' as usize, filled_bitmap(0, 0, 0))
+        .build();
+
+    assert_eq!(result.err().map(|e| e.kind()), Some(ErrorKind::InvalidAtlasParameters));
+}
+
+/// Building an atlas without any glyphs should fail.
+#[test]
+fn building_without_glyphs_should_fail() {
+    let result = BitmapFontAtlasBuilder::new(16, 2).build();
+
+    assert_eq!(result.err().map(|e| e.kind()), Some(ErrorKind::InvalidAtlasParameters));
+}
+
+/// A glyph bitmap larger than the glyph size would spill out of its slot, so building
+/// should fail.
+#[test]
+fn building_with_a_glyph_larger_than_the_glyph_size_should_fail() {
+    let result = BitmapFontAtlasBuilder::new(16, 2)
+        .format(PixelFormat::R8)
+        .glyph('A' as usize, filled_bitmap(15, 10, 255))
+        .build();
+
+    assert_eq!(result.err().map(|e| e.kind()), Some(ErrorKind::InvalidAtlasParameters));
+}
+
+/// A glyph bitmap whose data does not match the pixel format should fail to build.
+#[test]
+fn building_with_a_glyph_in_the_wrong_pixel_format_should_fail() {
+    let result = BitmapFontAtlasBuilder::new(16, 2)
+        .format(PixelFormat::Rgba8)
+        .glyph('A' as usize, filled_bitmap(10, 10, 255))
+        .build();
+
+    assert_eq!(result.err().map(|e| e.kind()), Some(ErrorKind::InvalidAtlasParameters));
+}
+
+/// Building an atlas with zero columns should fail.
+#[test]
+fn building_with_zero_columns_should_fail() {
+    let result = sample_builder().columns(0).build();
+
+    assert_eq!(result.err().map(|e| e.kind()), Some(ErrorKind::InvalidAtlasParameters));
+}
+
+/// Building an atlas with so many columns that the page size overflows, or exceeds the
+/// dimensions a PNG image can store, should fail instead of allocating the page.
+#[test]
+fn building_with_too_many_columns_should_fail() {
+    for &columns in [usize::MAX / 4, 1 << 30].iter() {
+        let result = sample_builder().columns(columns).build();
+
+        assert_eq!(result.err().map(|e| e.kind()), Some(ErrorKind::InvalidAtlasParameters));
+    }
+}
+
+/// The designated `.notdef` glyph should be recorded in the atlas.
+#[test]
+fn built_atlas_should_record_the_notdef_glyph() {
+    let atlas = sample_builder().notdef('.' as usize).build().unwrap();
+
+    assert_eq!(atlas.notdef, Some('.' as usize));
+    assert_eq!(atlas.glyph_or_fallback('z', &[]), atlas.glyph('.'));
+}
+
+/// Designating a `.notdef` glyph without a bitmap should fail.
+#[test]
+fn building_with_a_missing_notdef_glyph_should_fail() {
+    let result = sample_builder().notdef('z' as usize).build();
+
+    assert_eq!(result.err().map(|e| e.kind()), Some(ErrorKind::InvalidAtlasParameters));
+}
+
+/// Converting a bottom left atlas to a top left origin should produce the atlas built with
+/// a top left origin.
+#[test]
+fn built_atlas_converted_to_another_origin_should_match() {
+    let top_left = sample_builder().origin(Origin::TopLeft).build().unwrap();
+    let mut converted = sample_builder().origin(Origin::BottomLeft).build().unwrap();
+    converted.convert_origin(Origin::TopLeft);
+
+    assert_eq!(converted.origin, Origin::TopLeft);
+    assert_eq!(converted.pages, top_left.pages);
+    for (code_point, glyph) in top_left.glyph_metadata.iter() {
+        let converted_glyph = converted.glyph_metadata[code_point];
+        assert!(f32::abs(converted_glyph.y_min - glyph.y_min) < 1e-6);
+        assert_eq!(GlyphMetadata { y_min: glyph.y_min, ..converted_glyph }, *glyph);
+    }
+}
+
+/// A 16-bit atlas whose channels have two different bytes should be written with its
+/// channels big endian as PNG requires, and come back unchanged.
+#[test]
+fn built_16_bit_atlas_written_and_then_read_should_keep_the_byte_order() {
+    let data: Vec<u8> = (1..=(2 * 3 * 4)).collect();
+    let expected_atlas = BitmapFontAtlasBuilder::new(8, 2)
+        .format(PixelFormat::R16)
+        .glyph('A' as usize, GlyphBitmap::new(data.clone(), 3, 4))
+        .build()
+        .unwrap();
+    let mut cursor = io::Cursor::new(vec![]);
+    bmfa::to_writer(&mut cursor, &expected_atlas).unwrap();
+    let result_atlas = bmfa::from_reader(&mut cursor).unwrap();
+
+    assert_eq!(result_atlas.pages, expected_atlas.pages);
+
+    let mut zip_file = zip::ZipArchive::new(cursor).unwrap();
+    let mut png_data = vec![];
+    io::Read::read_to_end(&mut zip_file.by_name("atlas.png").unwrap(), &mut png_data).unwrap();
+    let png_image = image::load_from_memory(&png_data).unwrap().into_luma16();
+    let pixel = png_image.get_pixel(1, 1)[0];
+    assert_eq!(pixel, u16::from_ne_bytes([data[0], data[1]]));
+}
@@ -0,0 +1,155 @@
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+const SAMPLE_FILE: &str = "samples/freemono.bmfa";
+
+
+/// Run the command line tool with the given arguments.
+fn bmfa(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_bmfa")).args(args).output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+/// Return an empty scratch directory for one test.
+fn scratch_directory(name: &str) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!("bmfa_cli_{}", name));
+    let _ = std::fs::remove_dir_all(&directory);
+    std::fs::create_dir_all(&directory).unwrap();
+
+    directory
+}
+
+/// The info command should show the parameters and glyph coverage of an atlas.
+#[test]
+fn info_should_show_the_atlas_parameters() {
+    let output = bmfa(&["info", SAMPLE_FILE]);
+    let text = stdout(&output);
+
+    assert!(output.status.success());
+    assert!(text.contains("dimensions:      1024x1024"));
+    assert!(text.contains("origin:          BottomLeft"));
+    assert!(text.contains("glyphs:          224"));
+    assert!(text.contains("coverage:        U+0020-U+00FF"));
+}
+
+/// The glyphs command should print one line per glyph in code point order after a header.
+#[test]
+fn glyphs_should_print_the_glyph_table_in_code_point_order() {
+    let output = bmfa(&["glyphs", SAMPLE_FILE]);
+    let text = stdout(&output);
+    let lines: Vec<&str> = text.lines().collect();
+
+    assert!(output.status.success());
+    assert_eq!(lines.len(), 1 + 224);
+    assert!(lines[0].starts_with("code_point\tchar\tpage"));
+    assert!(lines[1].starts_with("U+0020\t\t"));
+    assert!(lines[34].starts_with("U+0041\tA\t"));
+}
+
+/// The validate command should succeed on a valid atlas.
+#[test]
+fn validate_should_accept_a_valid_atlas() {
+    let output = bmfa(&["validate", SAMPLE_FILE]);
+
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), format!("{}: ok\n", SAMPLE_FILE));
+}
+
+/// The validate command should report the diagnostics of an invalid atlas and exit with
+/// a failure status.
+#[test]
+fn validate_should_report_an_invalid_atlas() {
+    let directory = scratch_directory("validate");
+    let path = directory.join("invalid.bmfa");
+    let mut atlas = bmfa::load(SAMPLE_FILE).unwrap();
+    atlas.glyph_metadata.get_mut(&('A' as usize)).unwrap().row = atlas.rows;
+    bmfa::write_to_file(&path, &atlas).unwrap();
+    let output = bmfa(&["validate", path.to_str().unwrap()]);
+
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stdout(&output).lines().count(), 1);
+}
+
+/// Extracting an atlas and packing its files again should reproduce the atlas.
+#[test]
+fn extracted_atlas_packed_again_should_match() {
+    let directory = scratch_directory("extract");
+    let output = bmfa(&["extract", SAMPLE_FILE, directory.to_str().unwrap()]);
+    assert!(output.status.success());
+    assert!(directory.join("metadata.json").exists());
+    assert!(directory.join("atlas.png").exists());
+
+    let packed = directory.join("packed.bmfa");
+    let output = bmfa(&[
+        "pack",
+        packed.to_str().unwrap(),
+        directory.join("metadata.json").to_str().unwrap(),
+        directory.join("atlas.png").to_str().unwrap(),
+    ]);
+    assert!(output.status.success());
+
+    let expected_atlas = bmfa::load(SAMPLE_FILE).unwrap();
+    let result_atlas = bmfa::load(&packed).unwrap();
+    assert_eq!(result_atlas.metadata(), expected_atlas.metadata());
+    assert_eq!(result_atlas.pages, expected_atlas.pages);
+}
+
+/// Packing a different number of pages than the metadata declares should fail.
+#[test]
+fn pack_with_the_wrong_number_of_pages_should_fail() {
+    let directory = scratch_directory("pack");
+    bmfa(&["extract", SAMPLE_FILE, directory.to_str().unwrap()]);
+    let page = directory.join("atlas.png");
+    let output = bmfa(&[
+        "pack",
+        directory.join("packed.bmfa").to_str().unwrap(),
+        directory.join("metadata.json").to_str().unwrap(),
+        page.to_str().unwrap(),
+        page.to_str().unwrap(),
+    ]);
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(!directory.join("packed.bmfa").exists());
+}
+
+/// Packing to a path with another extension should write and print the `.bmfa` file.
+#[test]
+fn pack_should_print_the_path_it_writes() {
+    let directory = scratch_directory("pack_extension");
+    bmfa(&["extract", SAMPLE_FILE, directory.to_str().unwrap()]);
+    let output = bmfa(&[
+        "pack",
+        directory.join("packed.zip").to_str().unwrap(),
+        directory.join("metadata.json").to_str().unwrap(),
+        directory.join("atlas.png").to_str().unwrap(),
+    ]);
+    let packed = directory.join("packed.bmfa");
+
+    assert!(output.status.success());
+    assert_eq!(stdout(&output).trim_end(), packed.to_str().unwrap());
+    assert!(packed.exists());
+    assert!(!directory.join("packed.zip").exists());
+}
+
+/// An unknown command or a missing argument should fail with the usage message.
+#[test]
+fn unknown_commands_and_missing_arguments_should_print_the_usage() {
+    for args in [&["frobnicate"][..], &["info"][..], &[][..]].iter() {
+        let output = bmfa(args);
+
+        assert_eq!(output.status.code(), Some(2));
+        assert!(String::from_utf8_lossy(&output.stderr).contains("Usage: bmfa"));
+    }
+}
+
+/// Loading a missing file should fail with an error message.
+#[test]
+fn info_on_a_missing_file_should_fail() {
+    let output = bmfa(&["info", "samples/does_not_exist.bmfa"]);
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).starts_with("bmfa: File not found"));
+}
@@ -0,0 +1,499 @@
+use bmfa::generator::{self, GeneratorSettings};
+use std::io;
+
+const SAMPLE_FONT: &str = "samples/DejaVuSansMono.ttf";
+
+
+/// Generating an atlas from a font file that does not exist should fail.
+#[test]
+fn generating_from_a_nonexistent_font_file_should_fail() {
+    let settings = GeneratorSettings::default();
+    let maybe_atlas = generator::generate_from_file("DoesNotExist.ttf", &settings);
+
+    assert!(maybe_atlas.is_err());
+}
+
+/// Generating an atlas from data that is not a font should fail.
+#[test]
+fn generating_from_invalid_font_data_should_fail() {
+    let settings = GeneratorSettings::default();
+    let maybe_atlas = generator::generate(&[0, 1, 2, 3], &settings);
+
+    assert_eq!(maybe_atlas.err().map(|e| e.kind()), Some(bmfa::ErrorKind::CannotLoadFont));
+}
+
+/// The padding must leave room for a glyph inside each glyph slot.
+#[test]
+fn generating_with_padding_larger_than_the_slot_should_fail() {
+    let settings = GeneratorSettings::new(16, 16);
+    let maybe_atlas = generator::generate_from_file(SAMPLE_FONT, &settings);
+
+    assert_eq!(maybe_atlas.err().map(|e| e.kind()), Some(bmfa::ErrorKind::InvalidAtlasParameters));
+}
+
+/// A generated atlas should contain an entry for every requested code point the font
+/// provides a glyph for.
+#[test]
+fn generated_atlas_should_contain_every_requested_glyph() {
+    let settings = GeneratorSettings::default();
+    let atlas = generator::generate_from_file(SAMPLE_FONT, &settings).unwrap();
+
+    for code_point in settings.code_points.iter() {
+        assert!(atlas.glyph_metadata.contains_key(code_point));
+        assert_eq!(atlas.glyph_metadata[code_point].code_point, *code_point);
+    }
+}
+
+/// A generated atlas should satisfy the same dimension relations as a bmfa file.
+/// ```
+/// width == columns * slot glyph size
+/// height == rows * slot glyph size
+/// slot glyph size == padding + glyph size
+/// ```
+#[test]
+fn generated_atlas_dimensions_should_match_grid() {
+    let settings = GeneratorSettings::default();
+    let atlas = generator::generate_from_file(SAMPLE_FONT, &settings).unwrap();
+
+    assert_eq!(atlas.width, atlas.columns * atlas.slot_glyph_size);
+    assert_eq!(atlas.height, atlas.rows * atlas.slot_glyph_size);
+    assert_eq!(atlas.slot_glyph_size, atlas.padding + atlas.glyph_size);
+    assert_eq!(atlas.pages[0].len_bytes(), 4 * atlas.width * atlas.height);
+}
+
+/// Every glyph in a generated atlas should occupy its own slot inside the grid.
+#[test]
+fn generated_atlas_glyphs_should_occupy_distinct_slots() {
+    let settings = GeneratorSettings::default();
+    let atlas = generator::generate_from_file(SAMPLE_FONT, &settings).unwrap();
+    let mut slots: Vec<(usize, usize)> = atlas.glyph_metadata.values()
+        .map(|glyph| (glyph.row, glyph.column))
+        .collect();
+    slots.sort();
+    slots.dedup();
+
+    assert_eq!(slots.len(), atlas.glyph_metadata.len());
+    assert!(slots.iter().all(|&(row, column)| row < atlas.rows && column < atlas.columns));
+}
+
+/// A generated atlas should actually contain rasterized glyph pixels.
+#[test]
+fn generated_atlas_image_should_not_be_blank() {
+    let settings = GeneratorSettings::default();
+    let atlas = generator::generate_from_file(SAMPLE_FONT, &settings).unwrap();
+
+    assert!(atlas.pages[0].as_ref().iter().any(|&byte| byte != 0));
+}
+
+/// A generated atlas should survive being written out and read back in.
+#[test]
+fn generated_atlas_written_and_then_read_should_match() {
+    let settings = GeneratorSettings::default();
+    let expected_atlas = generator::generate_from_file(SAMPLE_FONT, &settings).unwrap();
+    let mut cursor = io::Cursor::new(vec![]);
+    bmfa::to_writer(&mut cursor, &expected_atlas).unwrap();
+    let result_atlas = bmfa::from_reader(&mut cursor).unwrap();
+
+    assert_eq!(result_atlas.metadata(), expected_atlas.metadata());
+    assert_eq!(result_atlas.pages, expected_atlas.pages);
+}
+
+/// A coverage atlas should not record any distance field parameters.
+#[test]
+fn generated_coverage_atlas_should_have_no_distance_field() {
+    let settings = GeneratorSettings::default();
+    let atlas = generator::generate_from_file(SAMPLE_FONT, &settings).unwrap();
+
+    assert_eq!(atlas.distance_field, None);
+}
+
+/// A distance field atlas should record the distance field range in its metadata.
+#[test]
+fn generated_distance_field_atlas_should_record_range() {
+    let distance_field = bmfa::DistanceField { range: 4.0 };
+    let settings = GeneratorSettings {
+        distance_field: Some(distance_field),
+        ..GeneratorSettings::default()
+    };
+    let atlas = generator::generate_from_file(SAMPLE_FONT, &settings).unwrap();
+
+    assert_eq!(atlas.distance_field, Some(distance_field));
+    assert_eq!(atlas.metadata().distance_field, Some(distance_field));
+}
+
+/// A distance field atlas should store values across the whole distance range: far outside
+/// the glyphs the values saturate to zero, and deep inside the glyphs they exceed one half.
+#[test]
+fn generated_distance_field_atlas_should_store_distances() {
+    let settings = GeneratorSettings {
+        distance_field: Some(bmfa::DistanceField { range: 4.0 }),
+        ..GeneratorSettings::default()
+    };
+    let atlas = generator::generate_from_file(SAMPLE_FONT, &settings).unwrap();
+    let values: Vec<u8> = atlas.pages[0].as_ref().iter().step_by(4).cloned().collect();
+
+    assert!(values.contains(&0));
+    assert!(values.iter().any(|&value| value > 128 && value < 255));
+    assert!(values.iter().any(|&value| value > 0 && value < 128));
+}
+
+/// A nonpositive distance field range is meaningless.
+#[test]
+fn generating_with_a_nonpositive_distance_field_range_should_fail() {
+    let settings = GeneratorSettings {
+        distance_field: Some(bmfa::DistanceField { range: 0.0 }),
+        ..GeneratorSettings::default()
+    };
+    let maybe_atlas = generator::generate_from_file(SAMPLE_FONT, &settings);
+
+    assert_eq!(maybe_atlas.err().map(|e| e.kind()), Some(bmfa::ErrorKind::InvalidAtlasParameters));
+}
+
+/// A distance field range whose margins fill the glyph slots, or an infinite one, leaves no
+/// room for the glyphs.
+#[test]
+fn generating_with_a_distance_field_range_too_large_for_the_slots_should_fail() {
+    for &range in [12.0, 7.5, f32::INFINITY].iter() {
+        let settings = GeneratorSettings {
+            distance_field: Some(bmfa::DistanceField { range: range }),
+            ..GeneratorSettings::new(16, 2)
+        };
+        let maybe_atlas = generator::generate_from_file(SAMPLE_FONT, &settings);
+
+        assert_eq!(maybe_atlas.err().map(|e| e.kind()), Some(bmfa::ErrorKind::InvalidAtlasParameters));
+    }
+}
+
+/// The distance field parameters should survive being written out and read back in.
+#[test]
+fn generated_distance_field_atlas_written_and_then_read_should_match() {
+    let settings = GeneratorSettings {
+        distance_field: Some(bmfa::DistanceField { range: 6.0 }),
+        ..GeneratorSettings::default()
+    };
+    let expected_atlas = generator::generate_from_file(SAMPLE_FONT, &settings).unwrap();
+    let mut cursor = io::Cursor::new(vec![]);
+    bmfa::to_writer(&mut cursor, &expected_atlas).unwrap();
+    let result_atlas = bmfa::from_reader(&mut cursor).unwrap();
+
+    assert_eq!(result_atlas.distance_field, expected_atlas.distance_field);
+    assert_eq!(result_atlas.pages, expected_atlas.pages);
+}
+
+fn packed_settings() -> GeneratorSettings {
+    GeneratorSettings {
+        layout: bmfa::AtlasLayout::Packed,
+        ..GeneratorSettings::default()
+    }
+}
+
+/// A grid atlas should not record pixel rectangles for its glyphs.
+#[test]
+fn generated_grid_atlas_glyphs_should_have_no_rects() {
+    let settings = GeneratorSettings::default();
+    let atlas = generator::generate_from_file(SAMPLE_FONT, &settings).unwrap();
+
+    assert_eq!(atlas.layout, bmfa::AtlasLayout::Grid);
+    assert!(atlas.glyph_metadata.values().all(|glyph| glyph.rect.is_none()));
+}
+
+/// Every glyph in a packed atlas should carry its own pixel rectangle inside the image.
+#[test]
+fn generated_packed_atlas_glyphs_should_have_rects_inside_the_image() {
+    let atlas = generator::generate_from_file(SAMPLE_FONT, &packed_settings()).unwrap();
+
+    assert_eq!(atlas.layout, bmfa::AtlasLayout::Packed);
+    for glyph in atlas.glyph_metadata.values() {
+        let rect = glyph.rect.unwrap();
+        assert!(rect.x + rect.width <= atlas.width);
+        assert!(rect.y + rect.height <= atlas.height);
+    }
+}
+
+/// The glyph rectangles in a packed atlas should not overlap each other.
+#[test]
+fn generated_packed_atlas_glyph_rects_should_not_overlap() {
+    let atlas = generator::generate_from_file(SAMPLE_FONT, &packed_settings()).unwrap();
+    let rects: Vec<bmfa::GlyphRect> = atlas.glyph_metadata.values()
+        .filter_map(|glyph| glyph.rect)
+        .filter(|rect| rect.width > 0 && rect.height > 0)
+        .collect();
+
+    for (i, rect1) in rects.iter().enumerate() {
+        for rect2 in rects[(i + 1)..].iter() {
+            let overlaps = rect1.x < rect2.x + rect2.width && rect2.x < rect1.x + rect1.width &&
+                rect1.y < rect2.y + rect2.height && rect2.y < rect1.y + rect1.height;
+            assert!(!overlaps, "{:?} overlaps {:?}", rect1, rect2);
+        }
+    }
+}
+
+/// Packing the glyphs tightly should take up less space than a grid of square slots.
+#[test]
+fn generated_packed_atlas_should_be_smaller_than_grid_atlas() {
+    let grid_atlas = generator::generate_from_file(SAMPLE_FONT, &GeneratorSettings::default()).unwrap();
+    let packed_atlas = generator::generate_from_file(SAMPLE_FONT, &packed_settings()).unwrap();
+
+    assert!(packed_atlas.width * packed_atlas.height < grid_atlas.width * grid_atlas.height);
+}
+
+/// A packed atlas should survive being written out and read back in.
+#[test]
+fn generated_packed_atlas_written_and_then_read_should_match() {
+    let expected_atlas = generator::generate_from_file(SAMPLE_FONT, &packed_settings()).unwrap();
+    let mut cursor = io::Cursor::new(vec![]);
+    bmfa::to_writer(&mut cursor, &expected_atlas).unwrap();
+    let result_atlas = bmfa::from_reader(&mut cursor).unwrap();
+
+    assert_eq!(result_atlas.metadata(), expected_atlas.metadata());
+    assert_eq!(result_atlas.pages, expected_atlas.pages);
+}
+
+fn multiple_page_settings(layout: bmfa::AtlasLayout) -> GeneratorSettings {
+    GeneratorSettings {
+        layout: layout,
+        max_page_size: 256,
+        ..GeneratorSettings::default()
+    }
+}
+
+/// Glyphs that do not fit on one grid page should spill over onto additional pages, with
+/// every glyph occupying its own slot on its page.
+#[test]
+fn generated_grid_atlas_should_spill_over_onto_multiple_pages() {
+    let settings = multiple_page_settings(bmfa::AtlasLayout::Grid);
+    let atlas = generator::generate_from_file(SAMPLE_FONT, &settings).unwrap();
+    let mut slots: Vec<(usize, usize, usize)> = atlas.glyph_metadata.values()
+        .map(|glyph| (glyph.page, glyph.row, glyph.column))
+        .collect();
+    slots.sort();
+    slots.dedup();
+
+    assert!(atlas.pages.len() > 1);
+    assert!(atlas.width <= 256 && atlas.height <= 256);
+    assert_eq!(slots.len(), atlas.glyph_metadata.len());
+    assert!(slots.iter().all(|&(page, _, _)| page < atlas.pages.len()));
+}
+
+/// Glyphs that do not fit on one packed page should spill over onto additional pages.
+#[test]
+fn generated_packed_atlas_should_spill_over_onto_multiple_pages() {
+    let settings = multiple_page_settings(bmfa::AtlasLayout::Packed);
+    let atlas = generator::generate_from_file(SAMPLE_FONT, &settings).unwrap();
+
+    assert!(atlas.pages.len() > 1);
+    assert!(atlas.width <= 256 && atlas.height <= 256);
+    assert!(atlas.glyph_metadata.values().all(|glyph| glyph.page < atlas.pages.len()));
+    for page in atlas.pages.iter() {
+        assert_eq!(page.len_bytes(), 4 * atlas.width * atlas.height);
+    }
+}
+
+/// A multiple page atlas should store each page in its own archive entry.
+#[test]
+fn generated_multiple_page_atlas_should_be_written_with_one_image_file_per_page() {
+    let settings = multiple_page_settings(bmfa::AtlasLayout::Grid);
+    let atlas = generator::generate_from_file(SAMPLE_FONT, &settings).unwrap();
+    let mut cursor = io::Cursor::new(vec![]);
+    bmfa::to_writer(&mut cursor, &atlas).unwrap();
+    let mut zip_file = zip::ZipArchive::new(cursor).unwrap();
+
+    assert_eq!(zip_file.len(), 1 + atlas.pages.len());
+    for page in 0..atlas.pages.len() {
+        assert!(zip_file.by_name(&format!("atlas_{}.png", page)).is_ok());
+    }
+}
+
+/// A multiple page atlas should survive being written out and read back in.
+#[test]
+fn generated_multiple_page_atlas_written_and_then_read_should_match() {
+    let settings = multiple_page_settings(bmfa::AtlasLayout::Packed);
+    let expected_atlas = generator::generate_from_file(SAMPLE_FONT, &settings).unwrap();
+    let mut cursor = io::Cursor::new(vec![]);
+    bmfa::to_writer(&mut cursor, &expected_atlas).unwrap();
+    let result_atlas = bmfa::from_reader(&mut cursor).unwrap();
+
+    assert_eq!(result_atlas.metadata(), expected_atlas.metadata());
+    assert_eq!(result_atlas.pages, expected_atlas.pages);
+}
+
+/// A maximum page size smaller than a glyph slot cannot hold any glyphs.
+#[test]
+fn generating_with_a_page_smaller_than_a_slot_should_fail() {
+    let settings = GeneratorSettings {
+        max_page_size: 32,
+        ..GeneratorSettings::default()
+    };
+    let maybe_atlas = generator::generate_from_file(SAMPLE_FONT, &settings);
+
+    assert_eq!(maybe_atlas.err().map(|e| e.kind()), Some(bmfa::ErrorKind::InvalidAtlasParameters));
+}
+
+const SAMPLE_PROPORTIONAL_FONT: &str = "samples/DejaVuSans.ttf";
+
+/// A proportional font with kerning should produce a kerning table pulling the glyphs of
+/// well known kerning pairs together.
+#[test]
+fn generated_atlas_should_record_kerning_pairs() {
+    let settings = GeneratorSettings {
+        kerning: true,
+        ..GeneratorSettings::default()
+    };
+    let atlas = generator::generate_from_file(SAMPLE_PROPORTIONAL_FONT, &settings).unwrap();
+
+    assert!(atlas.kern('A' as usize, 'V' as usize) < 0.0);
+    assert!(atlas.kern('T' as usize, 'o' as usize) < 0.0);
+}
+
+/// The kerning table should only be computed when requested, which it is not by default.
+#[test]
+fn generated_atlas_without_kerning_should_have_empty_kerning_table() {
+    let settings = GeneratorSettings::default();
+    let atlas = generator::generate_from_file(SAMPLE_PROPORTIONAL_FONT, &settings).unwrap();
+
+    assert!(atlas.kerning.is_empty());
+}
+
+/// The kerning table should survive being written out and read back in.
+#[test]
+fn generated_atlas_kerning_written_and_then_read_should_match() {
+    let settings = GeneratorSettings {
+        kerning: true,
+        ..GeneratorSettings::default()
+    };
+    let expected_atlas = generator::generate_from_file(SAMPLE_PROPORTIONAL_FONT, &settings).unwrap();
+    let mut cursor = io::Cursor::new(vec![]);
+    bmfa::to_writer(&mut cursor, &expected_atlas).unwrap();
+    let result_atlas = bmfa::from_reader(&mut cursor).unwrap();
+
+    assert!(!result_atlas.kerning.is_empty());
+    assert_eq!(result_atlas.kerning, expected_atlas.kerning);
+}
+
+/// A proportional font should record a separate advance for each glyph.
+#[test]
+fn generated_proportional_atlas_should_record_glyph_advances() {
+    let settings = GeneratorSettings::default();
+    let atlas = generator::generate_from_file(SAMPLE_PROPORTIONAL_FONT, &settings).unwrap();
+    let narrow = atlas.glyph_metadata[&('i' as usize)];
+    let wide = atlas.glyph_metadata[&('W' as usize)];
+
+    assert!(narrow.advance > 0.0);
+    assert!(narrow.advance < wide.advance);
+    assert!(atlas.glyph_metadata[&(' ' as usize)].advance > 0.0);
+}
+
+/// A monospace font should record the same advance for every glyph.
+#[test]
+fn generated_monospace_atlas_should_record_equal_glyph_advances() {
+    let settings = GeneratorSettings::default();
+    let atlas = generator::generate_from_file(SAMPLE_FONT, &settings).unwrap();
+    let advance = atlas.glyph_metadata[&('M' as usize)].advance;
+
+    for glyph in atlas.glyph_metadata.values() {
+        assert!(f32::abs(glyph.advance - advance) < 1e-6);
+    }
+}
+
+/// A generated atlas should record the font wide line metrics.
+#[test]
+fn generated_atlas_should_record_line_metrics() {
+    let settings = GeneratorSettings::default();
+    let atlas = generator::generate_from_file(SAMPLE_PROPORTIONAL_FONT, &settings).unwrap();
+
+    assert!(atlas.font_size > 0.0 && atlas.font_size <= atlas.glyph_size as f32);
+    assert!(atlas.ascent > 0.0);
+    assert!(atlas.descent < 0.0);
+    assert!(atlas.line_gap >= 0.0);
+    assert!(atlas.line_height() >= atlas.ascent - atlas.descent);
+}
+
+fn format_settings(format: bmfa::PixelFormat) -> GeneratorSettings {
+    GeneratorSettings {
+        format: format,
+        kerning: false,
+        ..GeneratorSettings::default()
+    }
+}
+
+/// A single channel atlas should store one byte per pixel.
+#[test]
+fn generated_single_channel_atlas_should_store_one_byte_per_pixel() {
+    let atlas = generator::generate_from_file(SAMPLE_FONT, &format_settings(bmfa::PixelFormat::R8)).unwrap();
+
+    assert_eq!(atlas.pages[0].format(), bmfa::PixelFormat::R8);
+    assert_eq!(atlas.pages[0].len_bytes(), atlas.width * atlas.height);
+}
+
+/// Every pixel format should store the same coverage, widened to the channel size of
+/// the format.
+#[test]
+fn generated_atlas_should_store_the_same_coverage_in_every_format() {
+    let expected_atlas = generator::generate_from_file(SAMPLE_FONT, &format_settings(bmfa::PixelFormat::Rgba8)).unwrap();
+    let formats = [
+        bmfa::PixelFormat::R8, bmfa::PixelFormat::Rg8, bmfa::PixelFormat::Rgb8,
+        bmfa::PixelFormat::R16, bmfa::PixelFormat::Rgba16,
+    ];
+    for &format in formats.iter() {
+        let atlas = generator::generate_from_file(SAMPLE_FONT, &format_settings(format)).unwrap();
+        let (expected_page, page) = (&expected_atlas.pages[0], &atlas.pages[0]);
+        assert_eq!(page.len_bytes(), format.bytes_per_pixel() * atlas.width * atlas.height);
+        for y in 0..atlas.height {
+            for x in 0..atlas.width {
+                let coverage = expected_page.pixel(x, y)[3];
+                let expected = match format.bytes_per_channel() {
+                    1 => vec![coverage; format.channel_count()],
+                    _ => (coverage as u16 * 257).to_ne_bytes().repeat(format.channel_count()),
+                };
+                assert_eq!(page.pixel(x, y), expected.as_slice());
+            }
+        }
+    }
+}
+
+/// Atlases in every pixel format should survive being written out and read back in.
+#[test]
+fn generated_atlas_in_every_format_written_and_then_read_should_match() {
+    let formats = [
+        bmfa::PixelFormat::R8, bmfa::PixelFormat::Rg8, bmfa::PixelFormat::Rgb8, bmfa::PixelFormat::Rgba8,
+        bmfa::PixelFormat::R16, bmfa::PixelFormat::Rg16, bmfa::PixelFormat::Rgb16, bmfa::PixelFormat::Rgba16,
+    ];
+    for &format in formats.iter() {
+        let expected_atlas = generator::generate_from_file(SAMPLE_FONT, &format_settings(format)).unwrap();
+        let mut cursor = io::Cursor::new(vec![]);
+        bmfa::to_writer(&mut cursor, &expected_atlas).unwrap();
+        let result_atlas = bmfa::from_reader(&mut cursor).unwrap();
+
+        assert_eq!(result_atlas.pages, expected_atlas.pages);
+    }
+}
+
+/// A single channel atlas with a top left origin should also survive being written out
+/// and read back in, since only bottom left atlases get flipped.
+#[test]
+fn generated_single_channel_top_left_atlas_written_and_then_read_should_match() {
+    let settings = GeneratorSettings {
+        origin: bmfa::Origin::TopLeft,
+        ..format_settings(bmfa::PixelFormat::R8)
+    };
+    let expected_atlas = generator::generate_from_file(SAMPLE_FONT, &settings).unwrap();
+    let mut cursor = io::Cursor::new(vec![]);
+    bmfa::to_writer(&mut cursor, &expected_atlas).unwrap();
+    let result_atlas = bmfa::from_reader(&mut cursor).unwrap();
+
+    assert_eq!(result_atlas.pages, expected_atlas.pages);
+}
+
+/// Generated atlases should have no `.notdef` glyph, since the `.notdef` glyph of a font
+/// has no code point, but one of their glyphs can be designated as one.
+#[test]
+fn generated_atlas_should_not_have_a_notdef_glyph() {
+    let mut atlas = generator::generate_from_file(SAMPLE_FONT, &GeneratorSettings::default()).unwrap();
+
+    assert_eq!(atlas.notdef, None);
+    assert!(!atlas.glyph_metadata.contains_key(&0));
+
+    atlas.notdef = Some('?' as usize);
+    assert_eq!(atlas.glyph_or_fallback('\u{4e2d}', &[]).map(|glyph| glyph.code_point), Some('?' as usize));
+    assert_eq!(atlas.validate(), vec![]);
+}
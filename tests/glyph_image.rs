@@ -0,0 +1,170 @@
+use bmfa::glyph_image::GlyphBounds;
+use bmfa::{BitmapFontAtlasBuilder, GlyphBitmap, GlyphRect, Origin, PixelFormat};
+
+const SAMPLE_FILE: &str = "samples/freemono.bmfa";
+
+
+/// Construct a glyph bitmap with two 8-bit channels whose pixels all differ.
+fn gradient_bitmap(width: usize, height: usize, x_offset: f32, y_offset: f32) -> GlyphBitmap {
+    let data = (0..(width * height))
+        .flat_map(|i| vec![i as u8, 255 - i as u8])
+        .collect();
+    let mut bitmap = GlyphBitmap::new(data, width, height);
+    bitmap.x_offset = x_offset;
+    bitmap.y_offset = y_offset;
+    bitmap.advance = width as f32 + 1.0;
+
+    bitmap
+}
+
+fn sample_builder() -> BitmapFontAtlasBuilder {
+    BitmapFontAtlasBuilder::new(16, 4)
+        .format(PixelFormat::Rg8)
+        .glyph('A' as usize, gradient_bitmap(10, 12, 1.0, 0.0))
+        .glyph('g' as usize, gradient_bitmap(7, 11, 0.0, -3.0))
+        .glyph('.' as usize, gradient_bitmap(2, 2, 2.0, 0.0))
+        .glyph(' ' as usize, GlyphBitmap::new(vec![], 0, 0))
+}
+
+/// Extracting the tight bounds of a glyph should return the bitmap the atlas was built
+/// from, in either origin.
+#[test]
+fn tight_glyph_bitmap_should_match_the_built_bitmap() {
+    for &origin in [Origin::TopLeft, Origin::BottomLeft].iter() {
+        let atlas = sample_builder().origin(origin).build().unwrap();
+        for &ch in ['A', 'g', '.'].iter() {
+            let bitmap = atlas.glyph_bitmap(ch as usize, GlyphBounds::Tight).unwrap();
+            let expected = match ch {
+                'A' => gradient_bitmap(10, 12, 1.0, 0.0),
+                'g' => gradient_bitmap(7, 11, 0.0, -3.0),
+                _ => gradient_bitmap(2, 2, 2.0, 0.0),
+            };
+
+            assert_eq!(bitmap, expected);
+        }
+    }
+}
+
+/// Extracting the slot of a glyph should return the whole slot with the glyph inset by
+/// half of the padding.
+#[test]
+fn slot_glyph_view_should_cover_the_glyph_slot() {
+    let atlas = sample_builder().build().unwrap();
+    let glyph = atlas.glyph_metadata[&('A' as usize)];
+    let view = atlas.glyph_view('A' as usize, GlyphBounds::Slot).unwrap();
+
+    assert_eq!(view.rect(), GlyphRect::new(glyph.column * 16, glyph.row * 16, 16, 16));
+    assert_eq!(view.format(), PixelFormat::Rg8);
+    assert_eq!(view.pixel(0, 0), &[0, 0]);
+    assert_eq!(view.pixel(2, 2), &[0, 255]);
+    assert_eq!(view.pixel(3, 2), &[1, 254]);
+    assert_eq!(view.pixel(2, 3), &[10, 245]);
+    assert_eq!(view.rows().count(), 16);
+    assert_eq!(view.to_vec().len(), 16 * 16 * 2);
+}
+
+/// A glyph view should read the same pixels from an atlas in either origin.
+#[test]
+fn glyph_views_should_not_depend_on_the_origin() {
+    let top_left = sample_builder().origin(Origin::TopLeft).build().unwrap();
+    let bottom_left = sample_builder().origin(Origin::BottomLeft).build().unwrap();
+    for &bounds in [GlyphBounds::Slot, GlyphBounds::Tight].iter() {
+        for code_point in top_left.glyph_metadata.keys() {
+            let expected = top_left.glyph_view(*code_point, bounds).unwrap();
+            let result = bottom_left.glyph_view(*code_point, bounds).unwrap();
+
+            assert_eq!(result.rect(), expected.rect());
+            assert_eq!(result.to_vec(), expected.to_vec());
+        }
+    }
+}
+
+/// The glyph bitmaps of either bounds should fit back into a builder with the same padding,
+/// and rebuild the same atlas page and tight glyph bitmaps.
+#[test]
+fn glyph_bitmaps_should_round_trip_through_the_builder() {
+    let atlas = sample_builder().build().unwrap();
+    for &bounds in [GlyphBounds::Slot, GlyphBounds::Tight].iter() {
+        let mut builder = BitmapFontAtlasBuilder::new(16, 4).format(PixelFormat::Rg8);
+        for &code_point in atlas.glyph_metadata.keys() {
+            builder = builder.glyph(code_point, atlas.glyph_bitmap(code_point, bounds).unwrap());
+        }
+        let rebuilt = builder.build().unwrap();
+
+        assert_eq!(rebuilt.pages, atlas.pages);
+        for &ch in ['A', 'g', '.'].iter() {
+            let expected = atlas.glyph_bitmap(ch as usize, GlyphBounds::Tight).unwrap();
+            let result = rebuilt.glyph_bitmap(ch as usize, GlyphBounds::Tight).unwrap();
+
+            assert_eq!(result, expected);
+        }
+    }
+}
+
+/// The slot bitmap of a glyph should leave out the padding around the glyph.
+#[test]
+fn slot_glyph_bitmap_should_leave_out_the_padding() {
+    let atlas = sample_builder().build().unwrap();
+    let bitmap = atlas.glyph_bitmap('A' as usize, GlyphBounds::Slot).unwrap();
+
+    assert_eq!((bitmap.width, bitmap.height), (atlas.glyph_size, atlas.glyph_size));
+    assert_eq!(&bitmap.data[..2], &[0, 255]);
+    assert_eq!((bitmap.x_offset, bitmap.y_offset), (1.0, 0.0));
+}
+
+/// A glyph without any covered pixels should have empty tight bounds.
+#[test]
+fn tight_bounds_of_a_blank_glyph_should_be_empty() {
+    let atlas = sample_builder().build().unwrap();
+    let bitmap = atlas.glyph_bitmap(' ' as usize, GlyphBounds::Tight).unwrap();
+
+    assert_eq!((bitmap.width, bitmap.height), (0, 0));
+    assert!(bitmap.data.is_empty());
+}
+
+/// The glyph views of the sample atlas should read the pixels of the glyph slots from the
+/// atlas image as it is stored in the PNG file.
+#[test]
+fn sample_glyph_views_should_read_the_glyph_slots() {
+    let atlas = bmfa::load(SAMPLE_FILE).unwrap();
+    let mut png_page = atlas.pages[0].clone();
+    png_page.convert_origin(Origin::TopLeft);
+    let view = atlas.glyph_view('A' as usize, GlyphBounds::Slot).unwrap();
+    let rect = view.rect();
+
+    for y in 0..view.height() {
+        for x in 0..view.width() {
+            assert_eq!(view.pixel(x, y), png_page.pixel(rect.x + x, rect.y + y));
+        }
+    }
+
+    let tight = atlas.glyph_view('A' as usize, GlyphBounds::Tight).unwrap().rect();
+    assert!(tight.width > 0 && tight.height > 0);
+    assert!(tight.x >= rect.x && tight.x + tight.width <= rect.x + rect.width);
+    assert!(tight.y >= rect.y && tight.y + tight.height <= rect.y + rect.height);
+}
+
+/// The slot of a glyph in a packed atlas should be its pixel rectangle.
+#[test]
+fn packed_glyph_view_should_cover_the_glyph_rectangle() {
+    let atlas = bmfa::bmfont::load("samples/bmfont/freemono.fnt").unwrap();
+    let glyph = atlas.glyph_metadata[&('A' as usize)];
+    let view = atlas.glyph_view('A' as usize, GlyphBounds::Slot).unwrap();
+
+    assert_eq!(Some(view.rect()), glyph.rect);
+}
+
+/// Looking up a glyph the atlas does not have, or one lying outside of its page, should
+/// return nothing.
+#[test]
+fn glyph_view_of_a_missing_glyph_should_be_none() {
+    let mut atlas = sample_builder().build().unwrap();
+    assert!(atlas.glyph_view('Z' as usize, GlyphBounds::Slot).is_none());
+    assert!(atlas.glyph_bitmap('Z' as usize, GlyphBounds::Tight).is_none());
+
+    atlas.glyph_metadata.get_mut(&('A' as usize)).unwrap().row = atlas.rows;
+    assert!(atlas.glyph_view('A' as usize, GlyphBounds::Slot).is_none());
+
+    atlas.glyph_metadata.get_mut(&('g' as usize)).unwrap().page = 1;
+    assert!(atlas.glyph_view('g' as usize, GlyphBounds::Slot).is_none());
+}
@@ -0,0 +1,261 @@
+use ab_glyph::{Font, FontRef, PxScale};
+use bmfa::generator::{self, GeneratorSettings};
+use bmfa::layout::{self, LayoutSettings};
+
+const SAMPLE_FILE: &str = "samples/freemono.bmfa";
+const SAMPLE_PROPORTIONAL_FONT: &str = "samples/DejaVuSans.ttf";
+
+
+fn sample_atlas() -> bmfa::BitmapFontAtlas {
+    bmfa::load(SAMPLE_FILE).unwrap()
+}
+
+/// Laying out an empty string should produce no glyphs.
+#[test]
+fn layout_of_an_empty_string_should_be_empty() {
+    let atlas = sample_atlas();
+    let glyphs = layout::layout(&atlas, "", &LayoutSettings::new(32.0));
+
+    assert!(glyphs.is_empty());
+}
+
+/// Every visible character should produce exactly one glyph quad, in order.
+#[test]
+fn layout_should_produce_one_quad_per_visible_character() {
+    let atlas = sample_atlas();
+    let glyphs = layout::layout(&atlas, "Hello, world", &LayoutSettings::new(32.0));
+    let code_points: Vec<usize> = glyphs.iter().map(|glyph| glyph.code_point).collect();
+    let expected: Vec<usize> = "Hello,world".chars().map(|ch| ch as usize).collect();
+
+    assert_eq!(code_points, expected);
+}
+
+/// The glyph quads on a line should advance from left to right.
+#[test]
+fn layout_should_advance_the_pen_from_left_to_right() {
+    let atlas = sample_atlas();
+    let glyphs = layout::layout(&atlas, "abcdef", &LayoutSettings::new(32.0));
+
+    for pair in glyphs.windows(2) {
+        assert!(pair[0].screen_rect.x < pair[1].screen_rect.x);
+    }
+}
+
+/// The first glyph of a line should start at the pen position from the settings.
+#[test]
+fn layout_should_start_at_the_pen_position() {
+    let atlas = sample_atlas();
+    let settings = LayoutSettings {
+        x: 100.0,
+        y: 200.0,
+        ..LayoutSettings::new(32.0)
+    };
+    let glyphs = layout::layout(&atlas, "A", &settings);
+    let glyph = atlas.glyph_metadata[&('A' as usize)];
+    let scale = 32.0 * atlas.slot_glyph_size as f32 / atlas.glyph_size as f32;
+    let bottom = glyphs[0].screen_rect.y + glyphs[0].screen_rect.height;
+
+    assert_eq!(glyphs[0].screen_rect.x, 100.0);
+    assert!(f32::abs(bottom - (200.0 - glyph.y_offset * scale)) < 1e-3);
+}
+
+/// A newline should move the pen back to the start of the next line.
+#[test]
+fn layout_should_move_to_the_next_line_on_newline() {
+    let atlas = sample_atlas();
+    let glyphs = layout::layout(&atlas, "AB\nAB", &LayoutSettings::new(32.0));
+
+    assert_eq!(glyphs.len(), 4);
+    assert_eq!(glyphs[2].screen_rect.x, glyphs[0].screen_rect.x);
+    assert_eq!(glyphs[3].screen_rect.x, glyphs[1].screen_rect.x);
+    assert!(glyphs[2].screen_rect.y > glyphs[0].screen_rect.y);
+}
+
+/// A tab should move the pen to the next tab stop.
+#[test]
+fn layout_should_move_to_the_next_tab_stop_on_tab() {
+    let atlas = sample_atlas();
+    let settings = LayoutSettings::new(32.0);
+    let tabbed = layout::layout(&atlas, "A\tB", &settings);
+    let spaced = layout::layout(&atlas, "    B", &settings);
+
+    assert_eq!(tabbed.len(), 2);
+    assert!(f32::abs(tabbed[1].screen_rect.x - spaced[0].screen_rect.x) < 1e-3);
+}
+
+/// A character missing from the atlas without any fallback glyph should produce no quad,
+/// but leave a gap.
+#[test]
+fn layout_should_skip_missing_glyphs_without_fallbacks() {
+    let atlas = sample_atlas();
+    let settings = LayoutSettings {
+        fallbacks: &[],
+        ..LayoutSettings::new(32.0)
+    };
+    let missing = layout::layout(&atlas, "A\u{4e2d}B", &settings);
+    let spaced = layout::layout(&atlas, "A B", &settings);
+
+    assert_eq!(missing.len(), 2);
+    assert!(f32::abs(missing[1].screen_rect.x - spaced[1].screen_rect.x) < 1e-3);
+}
+
+/// A character missing from the atlas should be drawn with the first fallback glyph the
+/// atlas has.
+#[test]
+fn layout_should_draw_missing_glyphs_with_a_fallback() {
+    let atlas = sample_atlas();
+    let missing = layout::layout(&atlas, "A\u{4e2d}B", &LayoutSettings::new(32.0));
+    let replaced = layout::layout(&atlas, "A?B", &LayoutSettings::new(32.0));
+
+    assert_eq!(missing, replaced);
+    assert_eq!(missing[1].code_point, '?' as usize);
+}
+
+/// A fallback chain built at runtime should be tried in order like a static one.
+#[test]
+fn layout_should_draw_missing_glyphs_with_a_runtime_fallback_chain() {
+    let atlas = sample_atlas();
+    let fallbacks: Vec<char> = String::from("\u{4e2e}*?").chars().collect();
+    let settings = LayoutSettings {
+        fallbacks: &fallbacks,
+        ..LayoutSettings::new(32.0)
+    };
+    let glyphs = layout::layout(&atlas, "A\u{4e2d}B", &settings);
+
+    assert_eq!(glyphs[1].code_point, '*' as usize);
+}
+
+/// Whitespace missing from the atlas should leave a gap instead of drawing a fallback glyph.
+#[test]
+fn layout_should_not_draw_fallbacks_for_missing_whitespace() {
+    let atlas = sample_atlas();
+    let glyphs = layout::layout(&atlas, "A\u{3000}B", &LayoutSettings::new(32.0));
+
+    assert_eq!(glyphs.len(), 2);
+}
+
+/// The texture coordinates of a glyph should select its slot in the atlas image.
+#[test]
+fn layout_texture_coordinates_should_select_the_glyph_slot() {
+    let atlas = sample_atlas();
+    let glyphs = layout::layout(&atlas, "A", &LayoutSettings::new(32.0));
+    let glyph = atlas.glyph_metadata[&('A' as usize)];
+    let uv_rect = glyphs[0].uv_rect;
+    let slot_width = 1.0 / atlas.columns as f32;
+    let slot_height = 1.0 / atlas.rows as f32;
+
+    // The sample atlas has its origin in the bottom left corner, so the rows count
+    // downwards from the top of the texture.
+    assert_eq!(atlas.origin, bmfa::Origin::BottomLeft);
+    assert!(f32::abs(uv_rect.x - glyph.column as f32 * slot_width) < 1e-6);
+    assert!(uv_rect.y >= 1.0 - (glyph.row + 1) as f32 * slot_height);
+    assert!(uv_rect.y + uv_rect.height <= 1.0 - glyph.row as f32 * slot_height + 1e-6);
+    assert!(uv_rect.width > 0.0 && uv_rect.width <= slot_width);
+}
+
+/// The glyph quads should scale linearly with the font size.
+#[test]
+fn layout_should_scale_with_the_font_size() {
+    let atlas = sample_atlas();
+    let small = layout::layout(&atlas, "W", &LayoutSettings::new(16.0));
+    let large = layout::layout(&atlas, "W", &LayoutSettings::new(32.0));
+
+    assert!(f32::abs(2.0 * small[0].screen_rect.width - large[0].screen_rect.width) < 1e-3);
+    assert!(f32::abs(2.0 * small[0].screen_rect.height - large[0].screen_rect.height) < 1e-3);
+}
+
+/// The layout should pull the glyphs of a kerning pair together by the amount recorded in
+/// the kerning table.
+#[test]
+fn layout_should_apply_kerning() {
+    let mut atlas = sample_atlas();
+    let settings = LayoutSettings::new(32.0);
+    let unkerned = layout::layout(&atlas, "AV", &settings);
+    atlas.kerning.insert(('A' as usize, 'V' as usize), -0.125);
+    let kerned = layout::layout(&atlas, "AV", &settings);
+    let scale = 32.0 * atlas.slot_glyph_size as f32 / atlas.glyph_size as f32;
+
+    assert_eq!(kerned[0].screen_rect, unkerned[0].screen_rect);
+    assert!(f32::abs(kerned[1].screen_rect.x - (unkerned[1].screen_rect.x - 0.125 * scale)) < 1e-3);
+}
+
+/// Kerning only applies between neighboring glyphs on the same line.
+#[test]
+fn layout_should_not_apply_kerning_across_lines() {
+    let mut atlas = sample_atlas();
+    let settings = LayoutSettings::new(32.0);
+    atlas.kerning.insert(('A' as usize, 'V' as usize), -0.125);
+    let glyphs = layout::layout(&atlas, "A\nV", &settings);
+
+    assert_eq!(glyphs[1].screen_rect.x, settings.x);
+}
+
+/// A newline should move the baseline down by the line height of the atlas.
+#[test]
+fn layout_should_move_down_by_the_line_height_on_newline() {
+    let atlas = sample_atlas();
+    let glyphs = layout::layout(&atlas, "A\nA", &LayoutSettings::new(32.0));
+    let scale = 32.0 * atlas.slot_glyph_size as f32 / atlas.font_size;
+    let distance = glyphs[1].screen_rect.y - glyphs[0].screen_rect.y;
+
+    assert!(f32::abs(distance - atlas.line_height() * scale) < 1e-3);
+}
+
+/// The pen should advance by the advance of each glyph rather than its width.
+#[test]
+fn layout_should_advance_the_pen_by_the_glyph_advance() {
+    let mut atlas = sample_atlas();
+    atlas.glyph_metadata.get_mut(&('A' as usize)).unwrap().advance = 0.25;
+    let glyphs = layout::layout(&atlas, "AB", &LayoutSettings::new(32.0));
+    let scale = 32.0 * atlas.slot_glyph_size as f32 / atlas.font_size;
+
+    assert!(f32::abs(glyphs[1].screen_rect.x - glyphs[0].screen_rect.x - 0.25 * scale) < 1e-3);
+}
+
+/// The texture coordinates of laid out glyphs should follow the origin of the atlas.
+#[test]
+fn layout_texture_coordinates_should_match_the_glyph_uv_rect() {
+    let atlas = sample_atlas();
+    let glyphs = layout::layout(&atlas, "Ag", &LayoutSettings::new(32.0));
+
+    for glyph in glyphs.iter() {
+        assert_eq!(Some(glyph.uv_rect), atlas.glyph_uv_rect(glyph.code_point, atlas.origin));
+    }
+}
+
+/// An atlas whose font size is not a positive number should produce no glyphs instead of
+/// quads with infinite or undefined coordinates.
+#[test]
+fn layout_with_an_invalid_font_size_should_be_empty() {
+    let mut atlas = sample_atlas();
+    atlas.font_size = 0.0;
+
+    assert!(layout::layout(&atlas, "Hello", &LayoutSettings::new(32.0)).is_empty());
+}
+
+/// A glyph generated from a font should keep its left side bearing, so its bitmap starts
+/// that far to the right of the pen, with or without a distance field.
+#[test]
+fn layout_should_place_generated_glyphs_by_their_left_side_bearing() {
+    let font_data = std::fs::read(SAMPLE_PROPORTIONAL_FONT).unwrap();
+    let font = FontRef::try_from_slice(&font_data).unwrap();
+    for &distance_field in [None, Some(bmfa::DistanceField { range: 4.0 })].iter() {
+        let settings = GeneratorSettings {
+            distance_field: distance_field,
+            ..GeneratorSettings::default()
+        };
+        let atlas = generator::generate(&font_data, &settings).unwrap();
+        let outline = font.outline_glyph(font.glyph_id('l').with_scale(PxScale::from(atlas.font_size))).unwrap();
+        let bearing = outline.px_bounds().min.x;
+        let margin = distance_field.map_or(0.0, |distance_field| f32::ceil(distance_field.range));
+        let layout_settings = LayoutSettings {
+            x: 100.0,
+            ..LayoutSettings::new(atlas.font_size)
+        };
+        let glyph = layout::layout(&atlas, "l", &layout_settings)[0];
+        let inset = (atlas.padding / 2) as f32;
+
+        assert!(bearing >= 1.0);
+        assert!(f32::abs(glyph.screen_rect.x + inset - (100.0 + bearing - margin)) < 1e-3);
+    }
+}
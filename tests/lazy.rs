@@ -0,0 +1,156 @@
+use bmfa::generator::{self, GeneratorSettings};
+use std::io;
+use std::io::Write;
+
+const SAMPLE_FILE: &str = "samples/freemono.bmfa";
+const SAMPLE_FONT: &str = "samples/DejaVuSansMono.ttf";
+
+
+/// Write an archive holding the metadata of the sample atlas and an atlas image that is
+/// not a PNG file.
+fn archive_with_corrupt_image() -> io::Cursor<Vec<u8>> {
+    let metadata = bmfa::load_metadata(SAMPLE_FILE).unwrap();
+    let mut writer = zip::ZipWriter::new(io::Cursor::new(vec![]));
+    let options = zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+    writer.start_file("metadata.json", options).unwrap();
+    serde_json::to_writer(&mut writer, &metadata).unwrap();
+    writer.start_file("atlas.png", options).unwrap();
+    writer.write_all(b"not a png file").unwrap();
+
+    io::Cursor::new(writer.finish().unwrap().into_inner())
+}
+
+/// Write an archive holding the metadata of the sample atlas and an atlas image that is
+/// smaller than the atlas.
+fn archive_with_wrong_sized_image() -> io::Cursor<Vec<u8>> {
+    let metadata = bmfa::load_metadata(SAMPLE_FILE).unwrap();
+    let mut image = vec![];
+    image::DynamicImage::new_rgba8(16, 16).write_to(&mut image, image::ImageOutputFormat::Png).unwrap();
+    let mut writer = zip::ZipWriter::new(io::Cursor::new(vec![]));
+    let options = zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+    writer.start_file("metadata.json", options).unwrap();
+    serde_json::to_writer(&mut writer, &metadata).unwrap();
+    writer.start_file("atlas.png", options).unwrap();
+    writer.write_all(&image).unwrap();
+
+    io::Cursor::new(writer.finish().unwrap().into_inner())
+}
+
+/// A lazy atlas should read the same metadata as a fully loaded atlas without decoding
+/// any pages.
+#[test]
+fn lazy_atlas_should_not_decode_pages_up_front() {
+    let expected_atlas = bmfa::load(SAMPLE_FILE).unwrap();
+    let lazy_atlas = bmfa::lazy::load(SAMPLE_FILE).unwrap();
+
+    assert_eq!(lazy_atlas.metadata(), &expected_atlas.metadata());
+    assert_eq!(lazy_atlas.page_count(), 1);
+    assert!(!lazy_atlas.is_page_decoded(0));
+}
+
+/// Requesting a page should decode it to the same image a fully loaded atlas holds.
+#[test]
+fn lazy_atlas_should_decode_pages_on_request() {
+    let expected_atlas = bmfa::load(SAMPLE_FILE).unwrap();
+    let mut lazy_atlas = bmfa::lazy::load(SAMPLE_FILE).unwrap();
+    let page = lazy_atlas.page(0).unwrap().clone();
+
+    assert_eq!(page, expected_atlas.pages[0]);
+    assert!(lazy_atlas.is_page_decoded(0));
+}
+
+/// Requesting a page the atlas does not have should fail.
+#[test]
+fn lazy_atlas_should_fail_to_decode_a_missing_page() {
+    let mut lazy_atlas = bmfa::lazy::load(SAMPLE_FILE).unwrap();
+    let result = lazy_atlas.page(1);
+
+    assert_eq!(result.err().map(|e| e.kind()), Some(bmfa::ErrorKind::FontAtlasImageNotFound));
+}
+
+/// Converting a lazy atlas into a full atlas should match loading the full atlas.
+#[test]
+fn lazy_atlas_into_atlas_should_match_full_atlas() {
+    let expected_atlas = bmfa::load(SAMPLE_FILE).unwrap();
+    let result_atlas = bmfa::lazy::load(SAMPLE_FILE).unwrap().into_atlas().unwrap();
+
+    assert_eq!(result_atlas.metadata(), expected_atlas.metadata());
+    assert_eq!(result_atlas.pages, expected_atlas.pages);
+}
+
+/// Only the requested pages of a multiple page atlas should get decoded.
+#[test]
+fn lazy_atlas_should_decode_only_the_requested_pages() {
+    let settings = GeneratorSettings {
+        max_page_size: 256,
+        kerning: false,
+        ..GeneratorSettings::default()
+    };
+    let expected_atlas = generator::generate_from_file(SAMPLE_FONT, &settings).unwrap();
+    let mut cursor = io::Cursor::new(vec![]);
+    bmfa::to_writer(&mut cursor, &expected_atlas).unwrap();
+    let mut lazy_atlas = bmfa::lazy::from_reader(cursor).unwrap();
+    let page = lazy_atlas.page(1).unwrap().clone();
+
+    assert_eq!(page, expected_atlas.pages[1]);
+    assert!(!lazy_atlas.is_page_decoded(0));
+    assert!(lazy_atlas.is_page_decoded(1));
+}
+
+/// A corrupt atlas image should only be reported once its page is requested.
+#[test]
+fn lazy_atlas_should_defer_image_errors_until_the_page_is_requested() {
+    let mut lazy_atlas = bmfa::lazy::from_reader(archive_with_corrupt_image()).unwrap();
+    let result = lazy_atlas.page(0);
+
+    assert_eq!(result.err().map(|e| e.kind()), Some(bmfa::ErrorKind::CannotLoadAtlasImage));
+}
+
+/// Strict reading should reject a page that does not match the atlas dimensions once it
+/// is decoded, as reading the whole atlas does.
+#[test]
+fn lazy_atlas_with_strict_reading_should_reject_a_page_of_the_wrong_size() {
+    let strict = bmfa::ReadOptions {
+        strict: true,
+        ..bmfa::ReadOptions::default()
+    };
+    let mut lazy_atlas = bmfa::lazy::from_reader_with_options(archive_with_wrong_sized_image(), &strict).unwrap();
+    let strict_atlas = bmfa::lazy::from_reader_with_options(archive_with_wrong_sized_image(), &strict).unwrap();
+    let mut lenient_atlas = bmfa::lazy::from_reader(archive_with_wrong_sized_image()).unwrap();
+
+    assert_eq!(lazy_atlas.page(0).err().map(|e| e.kind()), Some(bmfa::ErrorKind::InvalidAtlas));
+    assert!(!lazy_atlas.is_page_decoded(0));
+    assert_eq!(strict_atlas.into_atlas().err().map(|e| e.kind()), Some(bmfa::ErrorKind::InvalidAtlas));
+    assert_eq!(lenient_atlas.page(0).map(|page| page.width()).ok(), Some(16));
+    assert_eq!(
+        bmfa::from_reader_with_options(archive_with_wrong_sized_image(), &strict).err().map(|e| e.kind()),
+        Some(bmfa::ErrorKind::InvalidAtlas)
+    );
+}
+
+/// Loading only the metadata should match the metadata of the fully loaded atlas.
+#[test]
+fn load_metadata_should_match_full_atlas() {
+    let expected_atlas = bmfa::load(SAMPLE_FILE).unwrap();
+    let metadata = bmfa::load_metadata(SAMPLE_FILE).unwrap();
+
+    assert_eq!(metadata, expected_atlas.metadata());
+}
+
+/// Loading only the metadata should succeed even when the atlas image is corrupt, since
+/// the image is never decoded.
+#[test]
+fn metadata_from_reader_should_not_decode_the_atlas_image() {
+    let metadata = bmfa::metadata_from_reader(archive_with_corrupt_image());
+
+    assert!(metadata.is_ok());
+    assert!(bmfa::from_reader(archive_with_corrupt_image()).is_err());
+}
+
+/// Loading the metadata of a nonexistent file should fail.
+#[test]
+fn load_metadata_of_a_nonexistent_file_should_fail() {
+    let result = bmfa::load_metadata("DoesNotExist.bmfa");
+
+    assert_eq!(result.err().map(|e| e.kind()), Some(bmfa::ErrorKind::FileNotFound));
+}
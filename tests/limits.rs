@@ -0,0 +1,325 @@
+use bmfa::{BitmapFontAtlasBuilder, ErrorKind, GlyphBitmap, PixelFormat, ReadLimits, ReadOptions};
+use std::io;
+
+const SAMPLE_FILE: &str = "samples/freemono.bmfa";
+
+
+fn sample_archive() -> io::Cursor<Vec<u8>> {
+    io::Cursor::new(std::fs::read(SAMPLE_FILE).unwrap())
+}
+
+fn options_with_limits(limits: ReadLimits) -> ReadOptions {
+    ReadOptions {
+        limits: limits,
+        ..ReadOptions::default()
+    }
+}
+
+/// Write a small atlas out into memory.
+fn small_archive() -> Vec<u8> {
+    let atlas = BitmapFontAtlasBuilder::new(8, 2)
+        .format(PixelFormat::R8)
+        .glyph('A' as usize, GlyphBitmap::new(vec![255; 4 * 5], 4, 5))
+        .glyph('B' as usize, GlyphBitmap::new(vec![128; 5 * 6], 5, 6))
+        .build()
+        .unwrap();
+    let mut cursor = io::Cursor::new(vec![]);
+    bmfa::to_writer(&mut cursor, &atlas).unwrap();
+
+    cursor.into_inner()
+}
+
+/// Write an archive holding the sample atlas image and metadata declaring the given number
+/// of pages.
+fn archive_with_page_count(page_count: usize) -> io::Cursor<Vec<u8>> {
+    let mut metadata = bmfa::load_metadata(SAMPLE_FILE).unwrap();
+    metadata.page_count = page_count;
+    let atlas = bmfa::load(SAMPLE_FILE).unwrap();
+    let mut writer = zip::ZipWriter::new(io::Cursor::new(vec![]));
+    let options = zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+    writer.start_file("metadata.json", options).unwrap();
+    serde_json::to_writer(&mut writer, &metadata).unwrap();
+    writer.start_file("atlas.png", options).unwrap();
+    let mut png = vec![];
+    image::codecs::png::PngEncoder::new(&mut png)
+        .encode(atlas.pages[0].as_ref(), atlas.width as u32, atlas.height as u32, image::ColorType::Rgba8)
+        .unwrap();
+    io::Write::write_all(&mut writer, &png).unwrap();
+
+    io::Cursor::new(writer.finish().unwrap().into_inner())
+}
+
+/// Compute the CRC-32 of a PNG chunk.
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffff_u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xedb8_8320 } else { crc >> 1 };
+        }
+    }
+
+    !crc
+}
+
+/// Append a chunk to a PNG file.
+fn push_png_chunk(png: &mut Vec<u8>, chunk_type: &[u8], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(chunk_type);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// Write a PNG file consisting of an interlaced RGBA header of the given dimensions
+/// followed by an empty image data chunk.
+fn interlaced_png_header(width: u32, height: u32) -> Vec<u8> {
+    let mut png = vec![0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+    let mut header = vec![];
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    // Bit depth 8, RGBA, deflate, adaptive filtering, Adam7 interlacing.
+    header.extend_from_slice(&[8, 6, 0, 0, 1]);
+    push_png_chunk(&mut png, b"IHDR", &header);
+    push_png_chunk(&mut png, b"IDAT", &[]);
+    push_png_chunk(&mut png, b"IEND", &[]);
+
+    png
+}
+
+/// Replace the atlas image of the small atlas archive.
+fn small_archive_with_image(png: &[u8]) -> io::Cursor<Vec<u8>> {
+    let metadata = bmfa::metadata_from_reader(io::Cursor::new(small_archive())).unwrap();
+    let mut writer = zip::ZipWriter::new(io::Cursor::new(vec![]));
+    let options = zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+    writer.start_file("metadata.json", options).unwrap();
+    serde_json::to_writer(&mut writer, &metadata).unwrap();
+    writer.start_file("atlas.png", options).unwrap();
+    io::Write::write_all(&mut writer, png).unwrap();
+
+    io::Cursor::new(writer.finish().unwrap().into_inner())
+}
+
+/// The sample atlas should load within the default limits.
+#[test]
+fn sample_atlas_should_load_within_the_default_limits() {
+    let atlas = bmfa::from_reader_with_options(sample_archive(), &ReadOptions::default());
+
+    assert!(atlas.is_ok());
+}
+
+/// An atlas image wider or taller than the limit should be rejected.
+#[test]
+fn atlas_with_image_larger_than_the_dimension_limit_should_fail() {
+    let options = options_with_limits(ReadLimits {
+        max_image_dimension: 1023,
+        ..ReadLimits::default()
+    });
+    let result = bmfa::from_reader_with_options(sample_archive(), &options);
+
+    assert_eq!(result.err().map(|e| e.kind()), Some(ErrorKind::ImageTooLarge));
+}
+
+/// Decoding atlas images larger than the decompressed size limit should be rejected, while
+/// images exactly at the limit should load.
+#[test]
+fn atlas_with_images_exceeding_the_decompressed_size_limit_should_fail() {
+    let len_bytes = 4 * 1024 * 1024;
+    let options = options_with_limits(ReadLimits {
+        max_decompressed_size: len_bytes - 1,
+        ..ReadLimits::default()
+    });
+    let result = bmfa::from_reader_with_options(sample_archive(), &options);
+    assert_eq!(result.err().map(|e| e.kind()), Some(ErrorKind::ImageTooLarge));
+
+    let options = options_with_limits(ReadLimits {
+        max_decompressed_size: len_bytes,
+        ..ReadLimits::default()
+    });
+    let result = bmfa::from_reader_with_options(sample_archive(), &options);
+    assert!(result.is_ok());
+}
+
+/// A lazy atlas should enforce the decompressed size limit when it decodes a page.
+#[test]
+fn lazy_atlas_should_enforce_the_decompressed_size_limit() {
+    let options = options_with_limits(ReadLimits {
+        max_decompressed_size: 4 * 1024 * 1024 - 1,
+        ..ReadLimits::default()
+    });
+    let mut atlas = bmfa::lazy::from_reader_with_options(sample_archive(), &options).unwrap();
+
+    assert_eq!(atlas.page(0).err().map(|e| e.kind()), Some(ErrorKind::ImageTooLarge));
+}
+
+/// An atlas with more glyphs than the limit should be rejected.
+#[test]
+fn atlas_with_more_glyphs_than_the_limit_should_fail() {
+    let options = options_with_limits(ReadLimits {
+        max_glyph_count: 16,
+        ..ReadLimits::default()
+    });
+    let result = bmfa::from_reader_with_options(sample_archive(), &options);
+
+    assert_eq!(result.err().map(|e| e.kind()), Some(ErrorKind::TooManyGlyphs));
+}
+
+/// Metadata larger than the limit should be rejected before it is parsed.
+#[test]
+fn atlas_with_metadata_larger_than_the_limit_should_fail() {
+    let options = options_with_limits(ReadLimits {
+        max_metadata_size: 1024,
+        ..ReadLimits::default()
+    });
+    let result = bmfa::from_reader_with_options(sample_archive(), &options);
+
+    assert_eq!(result.err().map(|e| e.kind()), Some(ErrorKind::ArchiveEntryTooLarge));
+}
+
+/// The glyph limit should be enforced before the glyph table is built. The glyphs of this
+/// metadata cannot be deserialized at all, so only counting them can reject it.
+#[test]
+fn atlas_with_more_glyphs_than_the_limit_should_fail_before_building_the_glyph_table() {
+    let glyphs: Vec<String> = (0..17).map(|code_point| format!("\"{}\": null", code_point)).collect();
+    let metadata = format!("{{\"version\": 2, \"glyph_metadata\": {{{}}}}}", glyphs.join(", "));
+    let mut writer = zip::ZipWriter::new(io::Cursor::new(vec![]));
+    writer.start_file("metadata.json", zip::write::FileOptions::default()).unwrap();
+    io::Write::write_all(&mut writer, metadata.as_bytes()).unwrap();
+    let archive = io::Cursor::new(writer.finish().unwrap().into_inner());
+
+    let options = options_with_limits(ReadLimits {
+        max_glyph_count: 16,
+        ..ReadLimits::default()
+    });
+    let result = bmfa::metadata_from_reader_with_options(archive.clone(), &options);
+    assert_eq!(result.err().map(|e| e.kind()), Some(ErrorKind::TooManyGlyphs));
+
+    let result = bmfa::metadata_from_reader(archive);
+    assert_eq!(result.err().map(|e| e.kind()), Some(ErrorKind::CannotLoadAtlasMetadata));
+}
+
+/// Reading only the metadata should apply the limits of the options too, both tighter
+/// and looser than the default ones.
+#[test]
+fn metadata_should_be_read_within_the_limits_of_the_options() {
+    let strict = options_with_limits(ReadLimits {
+        max_glyph_count: 16,
+        ..ReadLimits::default()
+    });
+    let result = bmfa::metadata_from_reader_with_options(sample_archive(), &strict);
+    assert_eq!(result.err().map(|e| e.kind()), Some(ErrorKind::TooManyGlyphs));
+
+    let result = bmfa::load_metadata_with_options(SAMPLE_FILE, &options_with_limits(ReadLimits::unlimited()));
+    assert_eq!(result.unwrap(), bmfa::load_metadata(SAMPLE_FILE).unwrap());
+}
+
+/// An interlaced atlas image whose header declares huge dimensions should be rejected from
+/// its header alone, before the image decoder allocates anything for it. The limits of the
+/// fuzz target are checked as well.
+#[test]
+fn atlas_with_huge_interlaced_image_header_should_fail_before_decoding() {
+    let archive = small_archive_with_image(&interlaced_png_header(65535, 65535));
+    let result = bmfa::from_reader(archive.clone());
+    assert_eq!(result.err().map(|e| e.kind()), Some(ErrorKind::ImageTooLarge));
+
+    let options = options_with_limits(ReadLimits {
+        max_image_dimension: usize::MAX,
+        ..ReadLimits::default()
+    });
+    let result = bmfa::from_reader_with_options(archive.clone(), &options);
+    assert_eq!(result.err().map(|e| e.kind()), Some(ErrorKind::ImageTooLarge));
+
+    let options = options_with_limits(ReadLimits {
+        max_image_dimension: usize::MAX,
+        max_decompressed_size: 1 << 20,
+        ..ReadLimits::default()
+    });
+    let archive = small_archive_with_image(&interlaced_png_header(u32::MAX >> 1, 1));
+    let result = bmfa::from_reader_with_options(archive, &options);
+    assert_eq!(result.err().map(|e| e.kind()), Some(ErrorKind::ImageTooLarge));
+
+    let fuzz_options = ReadOptions {
+        strict: true,
+        limits: ReadLimits {
+            max_image_dimension: 1024,
+            max_decompressed_size: 16 << 20,
+            max_glyph_count: 1 << 16,
+            max_metadata_size: 1 << 20,
+            max_image_entry_size: 16 << 20,
+        },
+    };
+    let archive = small_archive_with_image(&interlaced_png_header(65535, 65535));
+    let result = bmfa::from_reader_with_options(archive.clone(), &fuzz_options);
+    assert_eq!(result.err().map(|e| e.kind()), Some(ErrorKind::ImageTooLarge));
+    let mut atlas = bmfa::lazy::from_reader_with_options(archive, &fuzz_options).unwrap();
+    assert_eq!(atlas.page(0).err().map(|e| e.kind()), Some(ErrorKind::ImageTooLarge));
+}
+
+/// An atlas image file larger than the limit should be rejected before it is decoded.
+#[test]
+fn atlas_with_image_file_larger_than_the_limit_should_fail() {
+    let options = options_with_limits(ReadLimits {
+        max_image_entry_size: 1024,
+        ..ReadLimits::default()
+    });
+    let result = bmfa::from_reader_with_options(sample_archive(), &options);
+
+    assert_eq!(result.err().map(|e| e.kind()), Some(ErrorKind::ArchiveEntryTooLarge));
+}
+
+/// Metadata declaring more pages than the archive has entries should be rejected without
+/// allocating anything for the pages.
+#[test]
+fn atlas_declaring_more_pages_than_archive_entries_should_fail() {
+    let page_count = usize::MAX / 2;
+    let result = bmfa::from_reader(archive_with_page_count(page_count));
+    assert_eq!(result.err().map(|e| e.kind()), Some(ErrorKind::FontAtlasImageNotFound));
+
+    let result = bmfa::lazy::from_reader(archive_with_page_count(page_count));
+    assert_eq!(result.err().map(|e| e.kind()), Some(ErrorKind::FontAtlasImageNotFound));
+}
+
+/// Validating metadata whose dimensions would overflow should report them instead of
+/// panicking.
+#[test]
+fn validating_metadata_with_huge_dimensions_should_not_overflow() {
+    let mut metadata = bmfa::load_metadata(SAMPLE_FILE).unwrap();
+    metadata.columns = usize::MAX;
+    metadata.padding = usize::MAX;
+
+    assert_eq!(metadata.validate().len(), 2);
+    for diagnostic in metadata.validate() {
+        assert!(!diagnostic.to_string().is_empty());
+    }
+}
+
+/// Truncated or corrupted archives should fail to load with an error rather than a panic.
+#[test]
+fn corrupted_archives_should_fail_without_panicking() {
+    let archive = small_archive();
+    let mut corrupted_archives = vec![];
+    for len in (0..archive.len()).step_by(7) {
+        corrupted_archives.push(archive[..len].to_vec());
+    }
+    for i in 0..archive.len() {
+        for &value in [0x00, 0xff, archive[i] ^ 0x80].iter() {
+            let mut corrupted = archive.clone();
+            corrupted[i] = value;
+            corrupted_archives.push(corrupted);
+        }
+    }
+
+    for corrupted in corrupted_archives {
+        let _ = bmfa::from_reader(io::Cursor::new(corrupted.clone()));
+        let _ = bmfa::from_reader_with_options(io::Cursor::new(corrupted.clone()), &ReadOptions {
+            strict: true,
+            ..ReadOptions::default()
+        });
+        if let Ok(mut atlas) = bmfa::lazy::from_reader(io::Cursor::new(corrupted)) {
+            for page in 0..atlas.page_count() {
+                let _ = atlas.page(page);
+            }
+        }
+    }
+}
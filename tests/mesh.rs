@@ -0,0 +1,236 @@
+use bmfa::layout::{self, LayoutSettings};
+use bmfa::mesh::{self, Batch, Mesh, MeshSettings, VertexAttribute, Winding};
+use bmfa::{BitmapFontAtlasBuilder, GlyphBitmap, Origin};
+
+const SAMPLE_FILE: &str = "samples/freemono.bmfa";
+
+
+fn sample_atlas() -> bmfa::BitmapFontAtlas {
+    bmfa::load(SAMPLE_FILE).unwrap()
+}
+
+fn layout_settings() -> LayoutSettings<'static> {
+    LayoutSettings {
+        x: 10.0,
+        y: 50.0,
+        ..LayoutSettings::new(32.0)
+    }
+}
+
+/// Return the two floats of an attribute of a vertex of a mesh.
+fn attribute(mesh: &Mesh, settings: &MeshSettings, vertex: usize, attribute: VertexAttribute) -> [f32; 2] {
+    let offset = vertex * settings.stride() + settings.offset(attribute).unwrap();
+
+    [mesh.vertices[offset], mesh.vertices[offset + 1]]
+}
+
+/// Return twice the signed area of a triangle of a mesh with the y-axis pointing up, which
+/// is positive when its corners go around counterclockwise.
+fn signed_area(mesh: &Mesh, settings: &MeshSettings, triangle: usize) -> f32 {
+    let flip = match settings.origin {
+        Origin::TopLeft => -1.0,
+        Origin::BottomLeft => 1.0,
+    };
+    let corner = |i: usize| {
+        let [x, y] = attribute(mesh, settings, mesh.indices[3 * triangle + i] as usize, VertexAttribute::Position);
+        (x, flip * y)
+    };
+    let ((x0, y0), (x1, y1), (x2, y2)) = (corner(0), corner(1), corner(2));
+
+    (x1 - x0) * (y2 - y0) - (x2 - x0) * (y1 - y0)
+}
+
+/// Every visible glyph should produce four vertices and two triangles with the default
+/// layout of a position, texture coordinates and a color.
+#[test]
+fn mesh_should_produce_a_quad_per_glyph() {
+    let atlas = sample_atlas();
+    let settings = MeshSettings::default();
+    let mesh = mesh::mesh(&atlas, "Hi there", &layout_settings(), &settings);
+
+    assert_eq!(settings.stride(), 8);
+    assert_eq!(mesh.vertices.len(), 7 * 4 * 8);
+    assert_eq!(mesh.indices.len(), 7 * 6);
+    assert_eq!(mesh.indices[..12], [0, 1, 2, 0, 2, 3, 4, 5, 6, 4, 6, 7]);
+    assert_eq!(mesh.batches, vec![Batch { page: 0, first_index: 0, index_count: 42 }]);
+}
+
+/// The vertices of a quad should sit on the corners of the glyph on the screen, and sample
+/// the corners of the glyph in the atlas.
+#[test]
+fn mesh_vertices_should_match_the_glyph_quads() {
+    let atlas = sample_atlas();
+    let settings = MeshSettings::default();
+    let glyph = layout::layout(&atlas, "A", &layout_settings())[0];
+    let mesh = mesh::mesh_glyphs(&atlas, &[glyph], &settings);
+    let (rect, uv) = (glyph.screen_rect, atlas.glyph_uv_rect('A' as usize, atlas.origin).unwrap());
+    let position = |vertex| attribute(&mesh, &settings, vertex, VertexAttribute::Position);
+    let tex_coord = |vertex| attribute(&mesh, &settings, vertex, VertexAttribute::TexCoord);
+
+    // The sample atlas has a bottom left origin, so the top of the glyph has the larger `v`.
+    assert_eq!(atlas.origin, Origin::BottomLeft);
+    assert_eq!(position(0), [rect.x, rect.y]);
+    assert_eq!(position(2), [rect.x + rect.width, rect.y + rect.height]);
+    assert_eq!(tex_coord(0), [uv.x, uv.y + uv.height]);
+    assert_eq!(tex_coord(2), [uv.x + uv.width, uv.y]);
+}
+
+/// The top left corner of a glyph should sample the top left corner of the glyph in the
+/// atlas page in the texture coordinates of either atlas origin.
+#[test]
+fn mesh_texture_coordinates_should_follow_the_atlas_origin() {
+    let builder = BitmapFontAtlasBuilder::new(16, 2)
+        .glyph('A' as usize, GlyphBitmap::new(vec![255; 4 * 10 * 12], 10, 12));
+    let top_left = builder.clone().origin(Origin::TopLeft).build().unwrap();
+    let bottom_left = builder.origin(Origin::BottomLeft).build().unwrap();
+    let settings = MeshSettings::default();
+    let top_left_mesh = mesh::mesh(&top_left, "A", &LayoutSettings::new(16.0), &settings);
+    let bottom_left_mesh = mesh::mesh(&bottom_left, "A", &LayoutSettings::new(16.0), &settings);
+
+    for vertex in 0..4 {
+        let [u, v] = attribute(&top_left_mesh, &settings, vertex, VertexAttribute::TexCoord);
+        let [other_u, other_v] = attribute(&bottom_left_mesh, &settings, vertex, VertexAttribute::TexCoord);
+
+        assert_eq!(u, other_u);
+        assert!(f32::abs(v - (1.0 - other_v)) < 1e-6);
+    }
+}
+
+/// Positions with a bottom left origin should be flipped by the height of the target.
+#[test]
+fn mesh_with_a_bottom_left_origin_should_flip_the_positions() {
+    let atlas = sample_atlas();
+    let top_left = MeshSettings::default();
+    let bottom_left = MeshSettings {
+        origin: Origin::BottomLeft,
+        height: 480.0,
+        ..MeshSettings::default()
+    };
+    let expected = mesh::mesh(&atlas, "Wg\nWg", &layout_settings(), &top_left);
+    let result = mesh::mesh(&atlas, "Wg\nWg", &layout_settings(), &bottom_left);
+
+    assert_eq!(result.indices, expected.indices);
+    for vertex in 0..(expected.vertices.len() / top_left.stride()) {
+        let [x, y] = attribute(&expected, &top_left, vertex, VertexAttribute::Position);
+
+        assert_eq!(attribute(&result, &bottom_left, vertex, VertexAttribute::Position), [x, 480.0 - y]);
+    }
+}
+
+/// Every triangle should go around in the requested winding order as seen on the screen,
+/// in either coordinate convention.
+#[test]
+fn mesh_triangles_should_follow_the_winding_order() {
+    let atlas = sample_atlas();
+    for &origin in [Origin::TopLeft, Origin::BottomLeft].iter() {
+        for &winding in [Winding::CounterClockwise, Winding::Clockwise].iter() {
+            let settings = MeshSettings {
+                origin: origin,
+                winding: winding,
+                ..MeshSettings::default()
+            };
+            let mesh = mesh::mesh(&atlas, "Hello", &layout_settings(), &settings);
+            for triangle in 0..(mesh.indices.len() / 3) {
+                let area = signed_area(&mesh, &settings, triangle);
+
+                match winding {
+                    Winding::CounterClockwise => assert!(area > 0.0),
+                    Winding::Clockwise => assert!(area < 0.0),
+                }
+            }
+        }
+    }
+}
+
+/// The vertices should interleave exactly the requested attributes, in order.
+#[test]
+fn mesh_should_interleave_the_requested_attributes() {
+    let atlas = sample_atlas();
+    let settings = MeshSettings {
+        attributes: &[VertexAttribute::Color, VertexAttribute::Page, VertexAttribute::Position],
+        color: [0.25, 0.5, 0.75, 1.0],
+        ..MeshSettings::default()
+    };
+    let mesh = mesh::mesh(&atlas, "AB", &layout_settings(), &settings);
+
+    assert_eq!(settings.stride(), 7);
+    assert_eq!(settings.offset(VertexAttribute::Position), Some(5));
+    assert_eq!(settings.offset(VertexAttribute::TexCoord), None);
+    assert_eq!(mesh.vertices.len(), 2 * 4 * 7);
+    for vertex in mesh.vertices.chunks(7) {
+        assert_eq!(vertex[..5], [0.25, 0.5, 0.75, 1.0, 0.0]);
+    }
+}
+
+/// A vertex layout built at runtime should work like a static one.
+#[test]
+fn mesh_should_accept_a_runtime_vertex_layout() {
+    let atlas = sample_atlas();
+    let attributes: Vec<VertexAttribute> = ["uv", "position"].iter()
+        .map(|name| match *name {
+            "uv" => VertexAttribute::TexCoord,
+            _ => VertexAttribute::Position,
+        })
+        .collect();
+    let settings = MeshSettings {
+        attributes: &attributes,
+        ..MeshSettings::default()
+    };
+    let static_settings = MeshSettings {
+        attributes: &[VertexAttribute::TexCoord, VertexAttribute::Position],
+        ..MeshSettings::default()
+    };
+
+    assert_eq!(settings.offset(VertexAttribute::Position), Some(2));
+    assert_eq!(
+        mesh::mesh(&atlas, "AB", &layout_settings(), &settings),
+        mesh::mesh(&atlas, "AB", &layout_settings(), &static_settings)
+    );
+}
+
+/// Glyphs on different atlas pages should be grouped into one batch per page.
+#[test]
+fn mesh_should_batch_the_glyphs_by_page() {
+    let atlas = sample_atlas();
+    let mut glyphs = layout::layout(&atlas, "abcde", &layout_settings());
+    glyphs[1].page = 1;
+    glyphs[3].page = 1;
+    let settings = MeshSettings {
+        attributes: &[VertexAttribute::Page],
+        ..MeshSettings::default()
+    };
+    let mesh = mesh::mesh_glyphs(&atlas, &glyphs, &settings);
+
+    assert_eq!(mesh.batches, vec![
+        Batch { page: 0, first_index: 0, index_count: 18 },
+        Batch { page: 1, first_index: 18, index_count: 12 },
+    ]);
+    for batch in mesh.batches.iter() {
+        for &index in mesh.indices[batch.first_index..(batch.first_index + batch.index_count)].iter() {
+            assert_eq!(mesh.vertices[index as usize], batch.page as f32);
+        }
+    }
+}
+
+/// The indices should only convert to 16 bits when every index fits.
+#[test]
+fn mesh_indices_should_convert_to_16_bits_when_they_fit() {
+    let atlas = sample_atlas();
+    let mut mesh = mesh::mesh(&atlas, "A", &layout_settings(), &MeshSettings::default());
+
+    assert_eq!(mesh.indices_u16(), Some(vec![0, 1, 2, 0, 2, 3]));
+
+    mesh.indices.push(65536);
+    assert_eq!(mesh.indices_u16(), None);
+}
+
+/// Meshing an empty string should produce empty buffers.
+#[test]
+fn mesh_of_an_empty_string_should_be_empty() {
+    let atlas = sample_atlas();
+    let mesh = mesh::mesh(&atlas, " \n\t", &layout_settings(), &MeshSettings::default());
+
+    assert!(mesh.vertices.is_empty());
+    assert!(mesh.indices.is_empty());
+    assert!(mesh.batches.is_empty());
+}
@@ -0,0 +1,60 @@
+use bmfa::packing::SkylinePacker;
+use bmfa::GlyphRect;
+
+
+fn overlaps(rect1: &GlyphRect, rect2: &GlyphRect) -> bool {
+    rect1.x < rect2.x + rect2.width && rect2.x < rect1.x + rect1.width &&
+        rect1.y < rect2.y + rect2.height && rect2.y < rect1.y + rect1.height
+}
+
+/// The first rectangle packed into an empty region should land in the top left corner.
+#[test]
+fn first_packed_rectangle_should_be_in_the_top_left_corner() {
+    let mut packer = SkylinePacker::new(64, 64);
+    let rect = packer.pack(10, 20).unwrap();
+
+    assert_eq!(rect, GlyphRect::new(0, 0, 10, 20));
+}
+
+/// A rectangle larger than the packing region should not fit.
+#[test]
+fn packing_a_rectangle_larger_than_the_region_should_fail() {
+    let mut packer = SkylinePacker::new(64, 64);
+
+    assert!(packer.pack(65, 1).is_none());
+    assert!(packer.pack(1, 65).is_none());
+}
+
+/// Rectangles that exactly tile the packing region should all fit.
+#[test]
+fn rectangles_that_tile_the_region_should_all_fit() {
+    let mut packer = SkylinePacker::new(64, 64);
+    for _ in 0..16 {
+        assert!(packer.pack(16, 16).is_some());
+    }
+
+    assert!(packer.pack(1, 1).is_none());
+}
+
+/// Packed rectangles should stay inside the packing region and never overlap each other.
+#[test]
+fn packed_rectangles_should_not_overlap() {
+    let mut packer = SkylinePacker::new(128, 128);
+    let mut rects = vec![];
+    for i in 0..200 {
+        let width = 3 + (i * 7) % 13;
+        let height = 2 + (i * 5) % 17;
+        if let Some(rect) = packer.pack(width, height) {
+            rects.push(rect);
+        }
+    }
+
+    assert!(rects.len() > 50);
+    for (i, rect1) in rects.iter().enumerate() {
+        assert!(rect1.x + rect1.width <= 128);
+        assert!(rect1.y + rect1.height <= 128);
+        for rect2 in rects[(i + 1)..].iter() {
+            assert!(!overlaps(rect1, rect2), "{:?} overlaps {:?}", rect1, rect2);
+        }
+    }
+}
@@ -0,0 +1,181 @@
+use bmfa::generator::{self, GeneratorSettings};
+use bmfa::layout::{self, LayoutSettings};
+use bmfa::render;
+use bmfa::{ErrorKind, GlyphRect, Origin};
+use image::RgbaImage;
+
+const SAMPLE_FILE: &str = "samples/freemono.bmfa";
+const SAMPLE_FONT: &str = "samples/DejaVuSansMono.ttf";
+const GOLDEN_FILE: &str = "samples/golden/freemono_hello.png";
+
+
+fn sample_atlas() -> bmfa::BitmapFontAtlas {
+    bmfa::load(SAMPLE_FILE).unwrap()
+}
+
+/// Return the alpha channel of the pixel of an atlas page at a position measured from the
+/// top of the page as it is stored in the PNG file.
+fn atlas_alpha(atlas: &bmfa::BitmapFontAtlas, x: usize, y: usize) -> u8 {
+    let mut page = atlas.pages[0].clone();
+    page.convert_origin(Origin::TopLeft);
+
+    page.pixel(x, y)[3]
+}
+
+/// Rendering at the size the atlas was rasterized at, with the glyphs on whole pixels,
+/// should copy the coverage of the atlas pixel for pixel.
+#[test]
+fn rendering_at_the_atlas_size_should_copy_the_glyph_coverage() {
+    let atlas = sample_atlas();
+    let settings = LayoutSettings {
+        x: 10.0,
+        y: 70.0,
+        ..LayoutSettings::new(atlas.font_size)
+    };
+    let mut target = RgbaImage::new(100, 100);
+    render::render(&atlas, "A", &settings, [255, 255, 255, 255], &mut target);
+    let glyph = layout::layout(&atlas, "A", &settings)[0];
+    let region = atlas.glyph_rect('A' as usize).unwrap();
+    let (left, top) = (glyph.screen_rect.x as u32, glyph.screen_rect.y as u32);
+
+    assert_eq!(glyph.screen_rect.width, region.width as f32);
+    for y in 0..region.height {
+        for x in 0..region.width {
+            let pixel = target.get_pixel(left + x as u32, top + y as u32);
+            assert_eq!(pixel[3], atlas_alpha(&atlas, region.x + x, region.y + y));
+        }
+    }
+}
+
+/// Rendering should blend the text color over the buffer by the glyph coverage, and leave
+/// the pixels outside of the glyphs alone.
+#[test]
+fn rendering_should_blend_the_color_over_the_buffer() {
+    let atlas = sample_atlas();
+    let settings = LayoutSettings {
+        x: 10.0,
+        y: 70.0,
+        ..LayoutSettings::new(atlas.font_size)
+    };
+    let background = image::Rgba([0, 0, 255, 255]);
+    let mut target = RgbaImage::from_pixel(100, 100, background);
+    render::render(&atlas, "A", &settings, [255, 0, 0, 255], &mut target);
+
+    let mut covered = 0;
+    for pixel in target.pixels() {
+        assert_eq!(pixel[3], 255);
+        assert_eq!(pixel[0] as u32 + pixel[2] as u32, 255);
+        if pixel[0] == 255 {
+            covered += 1;
+        }
+    }
+    assert!(covered > 0);
+    assert_eq!(target.get_pixel(0, 0), &background);
+    assert_eq!(target.get_pixel(99, 99), &background);
+}
+
+/// Rendering in a translucent color should scale the coverage by the alpha of the color.
+#[test]
+fn rendering_in_a_translucent_color_should_scale_the_coverage() {
+    let atlas = sample_atlas();
+    let settings = LayoutSettings::new(atlas.font_size);
+    let settings = LayoutSettings { x: 10.0, y: 70.0, ..settings };
+    let mut opaque = RgbaImage::new(100, 100);
+    let mut translucent = RgbaImage::new(100, 100);
+    render::render(&atlas, "A", &settings, [0, 0, 0, 255], &mut opaque);
+    render::render(&atlas, "A", &settings, [0, 0, 0, 128], &mut translucent);
+
+    for (opaque, translucent) in opaque.pixels().zip(translucent.pixels()) {
+        let expected = f32::round(opaque[3] as f32 * 128.0 / 255.0);
+        assert!(f32::abs(translucent[3] as f32 - expected) <= 1.0);
+    }
+}
+
+/// Rendering text partly outside of the buffer should clip it.
+#[test]
+fn rendering_outside_of_the_buffer_should_clip() {
+    let atlas = sample_atlas();
+    let settings = LayoutSettings {
+        x: -20.0,
+        y: 10.0,
+        ..LayoutSettings::new(32.0)
+    };
+    let mut target = RgbaImage::new(40, 20);
+    render::render(&atlas, "Wg\nWg", &settings, [255, 255, 255, 255], &mut target);
+
+    assert!(target.pixels().any(|pixel| pixel[3] > 0));
+}
+
+/// A glyph whose rectangle runs past the end of the address space should be skipped
+/// instead of overflowing.
+#[test]
+fn rendering_should_skip_a_glyph_rectangle_out_of_range() {
+    let mut atlas = sample_atlas();
+    atlas.glyph_metadata.get_mut(&('A' as usize)).unwrap().rect = Some(GlyphRect::new(usize::MAX, 0, 2, 2));
+    let mut target = RgbaImage::new(40, 20);
+    render::render(&atlas, "A", &LayoutSettings::new(16.0), [255, 255, 255, 255], &mut target);
+
+    assert!(target.pixels().all(|pixel| pixel[3] == 0));
+}
+
+/// Rendering into a buffer that does not match its dimensions should fail.
+#[test]
+fn rendering_into_a_buffer_of_the_wrong_size_should_fail() {
+    let atlas = sample_atlas();
+    let mut buffer = vec![0; 4 * 10 * 10 - 1];
+    let result = render::render_to_buffer(
+        &atlas, "A", &LayoutSettings::new(16.0), [255, 255, 255, 255], &mut buffer, 10, 10
+    );
+
+    assert_eq!(result.err().map(|e| e.kind()), Some(ErrorKind::InvalidRenderTarget));
+}
+
+/// Rendering from a signed distance field atlas should produce solid glyph interiors with
+/// a narrow antialiased edge.
+#[test]
+fn rendering_a_distance_field_atlas_should_produce_solid_glyphs() {
+    let settings = GeneratorSettings {
+        distance_field: Some(bmfa::DistanceField { range: 4.0 }),
+        ..GeneratorSettings::default()
+    };
+    let atlas = generator::generate_from_file(SAMPLE_FONT, &settings).unwrap();
+    let layout_settings = LayoutSettings {
+        x: 8.0,
+        y: 80.0,
+        ..LayoutSettings::new(96.0)
+    };
+    let mut target = RgbaImage::new(96, 100);
+    render::render(&atlas, "I", &layout_settings, [255, 255, 255, 255], &mut target);
+    let solid = target.pixels().filter(|pixel| pixel[3] == 255).count();
+    let partial = target.pixels().filter(|pixel| pixel[3] > 0 && pixel[3] < 255).count();
+
+    assert!(solid > 0);
+    assert!(partial < solid);
+}
+
+/// Rendering the sample text should match the golden image. Set `BMFA_BLESS` to write the
+/// golden image again after an intended change to the glyph metrics or the renderer.
+#[test]
+fn rendering_should_match_the_golden_image() {
+    let atlas = sample_atlas();
+    let settings = LayoutSettings {
+        x: 8.0,
+        y: 40.0,
+        ..LayoutSettings::new(24.0)
+    };
+    let mut target = RgbaImage::from_pixel(240, 84, image::Rgba([255, 255, 255, 255]));
+    render::render(&atlas, "Hello, world!\nAVATAR jumpy\tq_p", &settings, [0, 0, 0, 255], &mut target);
+
+    if std::env::var_os("BMFA_BLESS").is_some() {
+        std::fs::create_dir_all("samples/golden").unwrap();
+        target.save(GOLDEN_FILE).unwrap();
+    }
+    let golden = image::open(GOLDEN_FILE).unwrap().to_rgba8();
+
+    assert_eq!(golden.dimensions(), target.dimensions());
+    for (golden_pixel, pixel) in golden.pixels().zip(target.pixels()) {
+        for channel in 0..4 {
+            assert!((golden_pixel[channel] as i32 - pixel[channel] as i32).abs() <= 1);
+        }
+    }
+}
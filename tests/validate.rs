@@ -0,0 +1,238 @@
+use bmfa::generator::{self, GeneratorSettings};
+use bmfa::validate::Diagnostic;
+use bmfa::{AtlasLayout, ErrorKind, ReadOptions};
+use std::io;
+
+const SAMPLE_FILE: &str = "samples/freemono.bmfa";
+const SAMPLE_FONT: &str = "samples/DejaVuSansMono.ttf";
+
+
+fn sample_atlas() -> bmfa::BitmapFontAtlas {
+    bmfa::load(SAMPLE_FILE).unwrap()
+}
+
+/// The sample atlas should satisfy every invariant.
+#[test]
+fn sample_atlas_should_be_valid() {
+    let atlas = sample_atlas();
+
+    assert_eq!(atlas.validate(), vec![]);
+    assert_eq!(atlas.metadata().validate(), vec![]);
+}
+
+/// Generated atlases should satisfy every invariant, in every layout.
+#[test]
+fn generated_atlases_should_be_valid() {
+    for &layout in [AtlasLayout::Grid, AtlasLayout::Packed].iter() {
+        let settings = GeneratorSettings {
+            layout: layout,
+            max_page_size: 256,
+            kerning: false,
+            ..GeneratorSettings::default()
+        };
+        let atlas = generator::generate_from_file(SAMPLE_FONT, &settings).unwrap();
+
+        assert!(atlas.pages.len() > 1);
+        assert_eq!(atlas.validate(), vec![]);
+    }
+}
+
+/// An atlas imported from a BMFont descriptor should satisfy every invariant.
+#[test]
+fn imported_bmfont_atlas_should_be_valid() {
+    let atlas = bmfa::bmfont::load("samples/bmfont/freemono.fnt").unwrap();
+
+    assert_eq!(atlas.validate(), vec![]);
+}
+
+/// An atlas whose width does not match its grid of slots should be reported.
+#[test]
+fn validate_should_report_grid_dimension_mismatch() {
+    let mut metadata = sample_atlas().metadata();
+    metadata.width += 1;
+
+    assert!(matches!(metadata.validate()[..], [Diagnostic::GridDimensionMismatch { .. }]));
+}
+
+/// An atlas whose slot size is not the sum of its padding and glyph size should be reported.
+#[test]
+fn validate_should_report_slot_size_mismatch() {
+    let mut metadata = sample_atlas().metadata();
+    metadata.glyph_size -= 1;
+
+    assert_eq!(metadata.validate(), vec![Diagnostic::SlotSizeMismatch {
+        slot_glyph_size: 64,
+        padding: 3,
+        glyph_size: 60,
+    }]);
+}
+
+/// A font size that is not a positive number should be reported.
+#[test]
+fn validate_should_report_invalid_font_size() {
+    let mut metadata = sample_atlas().metadata();
+    for &font_size in [0.0, -12.0, f32::INFINITY].iter() {
+        metadata.font_size = font_size;
+
+        assert_eq!(metadata.validate(), vec![Diagnostic::InvalidFontSize { font_size: font_size }]);
+    }
+}
+
+/// A glyph stored past the last row of the grid should be reported.
+#[test]
+fn validate_should_report_glyph_outside_grid() {
+    let mut metadata = sample_atlas().metadata();
+    metadata.glyph_metadata.get_mut(&('A' as usize)).unwrap().row = metadata.rows;
+
+    assert_eq!(metadata.validate(), vec![Diagnostic::GlyphOutsideGrid {
+        code_point: 'A' as usize,
+        row: metadata.rows,
+        column: metadata.glyph_metadata[&('A' as usize)].column,
+    }]);
+}
+
+/// Two glyphs stored in the same slot should be reported.
+#[test]
+fn validate_should_report_duplicate_slots() {
+    let mut metadata = sample_atlas().metadata();
+    let a = metadata.glyph_metadata[&('A' as usize)];
+    let b = metadata.glyph_metadata.get_mut(&('B' as usize)).unwrap();
+    b.row = a.row;
+    b.column = a.column;
+
+    assert_eq!(metadata.validate(), vec![Diagnostic::DuplicateSlot {
+        code_point: 'B' as usize,
+        other_code_point: 'A' as usize,
+        page: 0,
+        row: a.row,
+        column: a.column,
+    }]);
+}
+
+/// A glyph stored under the wrong key of the glyph table should be reported.
+#[test]
+fn validate_should_report_code_point_mismatch() {
+    let mut metadata = sample_atlas().metadata();
+    metadata.glyph_metadata.get_mut(&('A' as usize)).unwrap().code_point = 'Z' as usize + 1000;
+
+    assert_eq!(metadata.validate(), vec![Diagnostic::CodePointMismatch {
+        key: 'A' as usize,
+        code_point: 'Z' as usize + 1000,
+    }]);
+}
+
+/// Normalized glyph parameters outside of the unit interval should be reported.
+#[test]
+fn validate_should_report_values_out_of_range() {
+    let mut metadata = sample_atlas().metadata();
+    metadata.glyph_metadata.get_mut(&('A' as usize)).unwrap().x_min = 1.5;
+    metadata.glyph_metadata.get_mut(&('A' as usize)).unwrap().height = -0.25;
+
+    assert_eq!(metadata.validate(), vec![
+        Diagnostic::ValueOutOfRange { code_point: 'A' as usize, field: "x_min", value: 1.5 },
+        Diagnostic::ValueOutOfRange { code_point: 'A' as usize, field: "height", value: -0.25 },
+    ]);
+}
+
+/// A `.notdef` glyph missing from the glyph table should be reported.
+#[test]
+fn validate_should_report_missing_notdef_glyph() {
+    let mut metadata = sample_atlas().metadata();
+    metadata.notdef = Some(0x4e2d);
+
+    assert_eq!(metadata.validate(), vec![Diagnostic::MissingNotdef { code_point: 0x4e2d }]);
+}
+
+/// A glyph stored on a page the atlas does not have should be reported.
+#[test]
+fn validate_should_report_glyph_page_out_of_range() {
+    let mut metadata = sample_atlas().metadata();
+    metadata.glyph_metadata.get_mut(&('A' as usize)).unwrap().page = 1;
+
+    assert_eq!(metadata.validate(), vec![Diagnostic::GlyphPageOutOfRange {
+        code_point: 'A' as usize,
+        page: 1,
+    }]);
+}
+
+/// A packed atlas glyph without a pixel rectangle inside its page should be reported.
+#[test]
+fn validate_should_report_packed_glyph_outside_page() {
+    let mut atlas = bmfa::bmfont::load("samples/bmfont/freemono.fnt").unwrap();
+    atlas.glyph_metadata.get_mut(&('A' as usize)).unwrap().rect = None;
+    let rect = atlas.glyph_metadata.get_mut(&('B' as usize)).unwrap().rect.as_mut().unwrap();
+    rect.x = atlas.width - rect.width + 1;
+
+    assert_eq!(atlas.validate(), vec![
+        Diagnostic::GlyphOutsidePage { code_point: 'A' as usize },
+        Diagnostic::GlyphOutsidePage { code_point: 'B' as usize },
+    ]);
+}
+
+/// A page whose buffer does not match the atlas dimensions should be reported.
+#[test]
+fn validate_should_report_image_dimension_mismatch() {
+    let mut atlas = sample_atlas();
+    let page = &atlas.pages[0];
+    let truncated = page.as_ref()[..(page.len_bytes() - 1)].to_vec();
+    atlas.pages[0] = bmfa::BitmapFontAtlasImage::new(truncated, page.width(), page.height(), page.format(), atlas.origin);
+
+    assert_eq!(atlas.validate(), vec![Diagnostic::ImageDimensionMismatch {
+        page: 0,
+        width: atlas.width,
+        height: atlas.height,
+        len_bytes: 4 * atlas.width * atlas.height - 1,
+    }]);
+}
+
+/// A page whose dimensions overflow the length of its buffer should be reported instead
+/// of panicking.
+#[test]
+fn validate_should_report_overflowing_image_dimensions() {
+    let mut atlas = sample_atlas();
+    let width = usize::MAX / 2;
+    atlas.pages[0] = bmfa::BitmapFontAtlasImage::new(vec![], width, 3, bmfa::PixelFormat::Rgba8, atlas.origin);
+
+    assert_eq!(atlas.validate(), vec![Diagnostic::ImageDimensionMismatch {
+        page: 0,
+        width: width,
+        height: 3,
+        len_bytes: 0,
+    }]);
+}
+
+/// Write out an atlas whose metadata violates an invariant.
+fn invalid_atlas_file() -> io::Cursor<Vec<u8>> {
+    let mut atlas = sample_atlas();
+    atlas.glyph_metadata.get_mut(&('A' as usize)).unwrap().row = atlas.rows;
+    let mut cursor = io::Cursor::new(vec![]);
+    bmfa::to_writer(&mut cursor, &atlas).unwrap();
+
+    cursor
+}
+
+/// An invalid atlas should still load by default.
+#[test]
+fn invalid_atlas_should_load_by_default() {
+    let atlas = bmfa::from_reader(invalid_atlas_file()).unwrap();
+
+    assert_eq!(atlas.validate().len(), 1);
+}
+
+/// Strict loading should reject an invalid atlas.
+#[test]
+fn invalid_atlas_should_fail_to_load_strictly() {
+    let options = ReadOptions { strict: true, ..ReadOptions::default() };
+    let result = bmfa::from_reader_with_options(invalid_atlas_file(), &options);
+
+    assert_eq!(result.err().map(|e| e.kind()), Some(ErrorKind::InvalidAtlas));
+}
+
+/// Strict loading should accept a valid atlas.
+#[test]
+fn valid_atlas_should_load_strictly() {
+    let options = ReadOptions { strict: true, ..ReadOptions::default() };
+    let atlas = bmfa::load_with_options(SAMPLE_FILE, &options);
+
+    assert!(atlas.is_ok());
+}